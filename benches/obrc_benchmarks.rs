@@ -2,31 +2,67 @@ use std::hint::black_box;
 
 use criterion::criterion_group;
 use criterion::criterion_main;
+use criterion::measurement::WallTime;
 use criterion::Bencher;
+use criterion::BenchmarkGroup;
 use criterion::Criterion;
 use obrc::solutions::solution_basic::SolutioBasic;
+use obrc::solutions::solution_mmap::SolutionMmap;
 use obrc::solutions::solution_naive::SolutioNaive;
+use obrc::solutions::solution_open_addressing::SolutionOpenAddressing;
 use obrc::solutions::solution_rayon_fxhash::SolutionRayonFxHash;
 use obrc::solutions::solver::Solver;
 
 fn benchmark_solution_basic(c: &mut Criterion) {
     c.bench_function("SolutioBasic", |b: &mut Bencher<'_>| {
-        b.iter(|| SolutioBasic::solve_obrc(black_box("resources/measurements_1000000.txt")))
+        b.iter(|| {
+            SolutioBasic::solve_obrc(black_box("resources/measurements_1000000.txt")).unwrap()
+        })
     });
 }
 
 fn benchmark_solution_naive(c: &mut Criterion) {
     c.bench_function("SolutioNaive", |b: &mut Bencher<'_>| {
-        b.iter(|| SolutioNaive::solve_obrc(black_box("resources/measurements_1000000.txt")))
+        b.iter(|| {
+            SolutioNaive::solve_obrc(black_box("resources/measurements_1000000.txt")).unwrap()
+        })
     });
 }
 
 fn benchmark_solution_rayon_fxhash(c: &mut Criterion) {
     c.bench_function("SolutionRayonFxHash", |b: &mut Bencher<'_>| {
-        b.iter(|| SolutionRayonFxHash::solve_obrc(black_box("resources/measurements_1000000.txt")))
+        b.iter(|| {
+            SolutionRayonFxHash::solve_obrc(black_box("resources/measurements_1000000.txt"))
+                .unwrap()
+        })
     });
 }
 
+fn benchmark_solution_mmap(c: &mut Criterion) {
+    c.bench_function("SolutionMmap", |b: &mut Bencher<'_>| {
+        b.iter(|| {
+            SolutionMmap::solve_obrc(black_box("resources/measurements_1000000.txt")).unwrap()
+        })
+    });
+}
+
+fn benchmark_solution_open_addressing(c: &mut Criterion) {
+    let mut group: BenchmarkGroup<'_, WallTime> = c.benchmark_group("OpenAddressingVsRayonFxHash");
+    group.bench_function("SolutionRayonFxHash", |b: &mut Bencher<'_>| {
+        b.iter(|| {
+            SolutionRayonFxHash::solve_obrc(black_box("resources/measurements_1000000.txt"))
+                .unwrap()
+        })
+    });
+    group.bench_function("SolutionOpenAddressing", |b: &mut Bencher<'_>| {
+        b.iter(|| {
+            SolutionOpenAddressing::solve_obrc(black_box("resources/measurements_1000000.txt"))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
 criterion_group! {
     name = benches_basic;
     config = Criterion::default().sample_size(20);
@@ -45,4 +81,22 @@ criterion_group! {
     targets = benchmark_solution_rayon_fxhash
 }
 
-criterion_main!(benches_basic, benches_naive, benches_rayon_fxhash);
+criterion_group! {
+    name = benches_mmap;
+    config = Criterion::default().sample_size(20);
+    targets = benchmark_solution_mmap
+}
+
+criterion_group! {
+    name = benches_open_addressing;
+    config = Criterion::default().sample_size(20);
+    targets = benchmark_solution_open_addressing
+}
+
+criterion_main!(
+    benches_basic,
+    benches_naive,
+    benches_rayon_fxhash,
+    benches_mmap,
+    benches_open_addressing
+);
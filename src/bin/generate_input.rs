@@ -1,473 +1,381 @@
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::BufReader;
 use std::io::BufWriter;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+use std::process::exit;
+use std::thread::available_parallelism;
 use std::time::Duration;
 use std::time::Instant;
 
+use clap::error::ErrorKind;
+use clap::CommandFactory;
 use clap::Parser;
-use rand::seq::SliceRandom;
-use rand_distr::Distribution;
-use rand_distr::Normal;
+use clap::ValueEnum;
+#[cfg(feature = "gzip")]
+use flate2::write::GzEncoder;
+use obrc::generator::corruption::Corruption;
+use obrc::generator::corruption::Corruptions;
+use obrc::generator::corruption::Defect;
+use obrc::generator::distribution::StationDistribution;
+use obrc::generator::distribution::TemperatureDistribution;
+use obrc::generator::measurements::MeasurementsGenerator;
+use obrc::generator::weather_stations::get_weather_stations;
+use obrc::generator::weather_stations::name_capacity;
+use obrc::generator::weather_stations::read_station_weights;
+use obrc::generator::weather_stations::read_weather_stations;
+use obrc::generator::weather_stations::WeatherStation;
+use obrc::generator::weather_stations::MAX_NAME_LENGTH;
+use obrc::solutions::error::ObrcError;
+#[cfg(feature = "zstd")]
+use obrc::utils::compression::ZstdFrameWriter;
+use obrc::utils::station_table::MAX_STATIONS;
 
-const STD_DEV: f64 = 10.0;
+#[derive(Parser)]
+#[command(name = "generate_input")]
+struct Cli {
+    #[arg(short, long, value_parser = clap::value_parser!(usize), default_value_t = 1_000_000_000_usize)]
+    size: usize,
+
+    /// Seed of the random number generator, for byte-for-byte reproducible output across runs and platforms
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of generating threads, all available cores by default; does not change the output
+    #[arg(short, long)]
+    threads: Option<usize>,
+
+    /// Synthesize this many weather stations with unique names instead of the 413 built-in ones
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..=MAX_STATIONS as i64))]
+    stations: Option<u16>,
+
+    /// Length in bytes of the synthesized station names, as `min..max` (both inclusive)
+    #[arg(long, requires = "stations", value_parser = parse_name_length, default_value = "1..100")]
+    name_length: RangeInclusive<usize>,
+
+    /// Read the weather stations from this `name;mean[;distribution]` catalog instead of the built-in ones
+    #[arg(long, conflicts_with = "stations")]
+    stations_file: Option<String>,
+
+    /// Distribution of the temperatures around the mean of every station, unless the catalog
+    /// has one: `normal:<σ>`, `uniform:<half width>`, `bimodal:<offset>:<σ>` or `student-t:<ν>:<scale>`
+    #[arg(long, default_value_t = TemperatureDistribution::default())]
+    distribution: TemperatureDistribution,
+
+    /// Distribution of the measurements among the stations: `uniform`, `zipf:<exponent>`
+    /// by rank in the station list, or `weights-from-file` with `--weights-file`
+    #[arg(long, default_value_t = StationDistribution::Uniform)]
+    station_distribution: StationDistribution,
+
+    /// Weights of the stations for `--station-distribution weights-from-file`, one `name;weight` per line
+    #[arg(long, required_if_eq("station_distribution", "weights-from-file"))]
+    weights_file: Option<String>,
+
+    /// Probability of every measurement to get a defect, listed in `measurements_<size>.manifest`
+    #[arg(long, value_parser = parse_rate)]
+    corrupt_rate: Option<f64>,
+
+    /// Kinds of defects to inject, all by default
+    #[arg(long, requires = "corrupt_rate", value_enum, value_delimiter = ',')]
+    corruption_kinds: Vec<Corruption>,
+
+    /// Compress the measurements into `measurements_<size>.txt.gz` or `.txt.zst`,
+    /// with the `gzip` or `zstd` feature
+    #[arg(long, value_enum)]
+    compress: Option<Compress>,
+
+    /// Compression level, 0 to 9 for gzip (6 by default) and 1 to 22 for zstd (3 by default)
+    #[arg(long, requires = "compress")]
+    level: Option<i32>,
 
-struct WeatherStation {
-    name: &'static str,
-    distr: Normal<f64>,
+    /// Number of rows of every independent zstd frame, so that frames can be decoded in parallel
+    #[arg(long, requires = "compress", default_value_t = 1 << 20, value_parser = clap::value_parser!(u64).range(1..))]
+    frame_rows: u64,
 }
 
-impl WeatherStation {
-    fn new(name: &'static str, mean: f64) -> WeatherStation {
-        WeatherStation {
-            name,
-            distr: Normal::new(mean, STD_DEV).unwrap(),
+/// Compression of the generated measurements.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Compress {
+    Gzip,
+    Zstd,
+}
+
+impl Compress {
+    fn name(&self) -> &'static str {
+        match self {
+            Compress::Gzip => "gzip",
+            Compress::Zstd => "zstd",
         }
     }
 
-    fn get_measurement(&self) -> f64 {
-        format!("{:.1$}", self.distr.sample(&mut rand::thread_rng()), 1)
-            .parse::<f64>()
-            .unwrap()
+    fn extension(&self) -> &'static str {
+        match self {
+            Compress::Gzip => "gz",
+            Compress::Zstd => "zst",
+        }
     }
-}
 
-fn get_weather_stations() -> Vec<WeatherStation> {
-    vec![
-        WeatherStation::new("Abha", 18.0),
-        WeatherStation::new("Abidjan", 26.0),
-        WeatherStation::new("Abéché", 29.4),
-        WeatherStation::new("Accra", 26.4),
-        WeatherStation::new("Addis Ababa", 16.0),
-        WeatherStation::new("Adelaide", 17.3),
-        WeatherStation::new("Aden", 29.1),
-        WeatherStation::new("Ahvaz", 25.4),
-        WeatherStation::new("Albuquerque", 14.0),
-        WeatherStation::new("Alexandra", 11.0),
-        WeatherStation::new("Alexandria", 20.0),
-        WeatherStation::new("Algiers", 18.2),
-        WeatherStation::new("Alice Springs", 21.0),
-        WeatherStation::new("Almaty", 10.0),
-        WeatherStation::new("Amsterdam", 10.2),
-        WeatherStation::new("Anadyr", -6.9),
-        WeatherStation::new("Anchorage", 2.8),
-        WeatherStation::new("Andorra la Vella", 9.8),
-        WeatherStation::new("Ankara", 12.0),
-        WeatherStation::new("Antananarivo", 17.9),
-        WeatherStation::new("Antsiranana", 25.2),
-        WeatherStation::new("Arkhangelsk", 1.3),
-        WeatherStation::new("Ashgabat", 17.1),
-        WeatherStation::new("Asmara", 15.6),
-        WeatherStation::new("Assab", 30.5),
-        WeatherStation::new("Astana", 3.5),
-        WeatherStation::new("Athens", 19.2),
-        WeatherStation::new("Atlanta", 17.0),
-        WeatherStation::new("Auckland", 15.2),
-        WeatherStation::new("Austin", 20.7),
-        WeatherStation::new("Baghdad", 22.77),
-        WeatherStation::new("Baguio", 19.5),
-        WeatherStation::new("Baku", 15.1),
-        WeatherStation::new("Baltimore", 13.1),
-        WeatherStation::new("Bamako", 27.8),
-        WeatherStation::new("Bangkok", 28.6),
-        WeatherStation::new("Bangui", 26.0),
-        WeatherStation::new("Banjul", 26.0),
-        WeatherStation::new("Barcelona", 18.2),
-        WeatherStation::new("Bata", 25.1),
-        WeatherStation::new("Batumi", 14.0),
-        WeatherStation::new("Beijing", 12.9),
-        WeatherStation::new("Beirut", 20.9),
-        WeatherStation::new("Belgrade", 12.5),
-        WeatherStation::new("Belize City", 26.7),
-        WeatherStation::new("Benghazi", 19.9),
-        WeatherStation::new("Bergen", 7.7),
-        WeatherStation::new("Berlin", 10.3),
-        WeatherStation::new("Bilbao", 14.7),
-        WeatherStation::new("Birao", 26.5),
-        WeatherStation::new("Bishkek", 11.3),
-        WeatherStation::new("Bissau", 27.0),
-        WeatherStation::new("Blantyre", 22.2),
-        WeatherStation::new("Bloemfontein", 15.6),
-        WeatherStation::new("Boise", 11.4),
-        WeatherStation::new("Bordeaux", 14.2),
-        WeatherStation::new("Bosaso", 30.0),
-        WeatherStation::new("Boston", 10.9),
-        WeatherStation::new("Bouaké", 26.0),
-        WeatherStation::new("Bratislava", 10.5),
-        WeatherStation::new("Brazzaville", 25.0),
-        WeatherStation::new("Bridgetown", 27.0),
-        WeatherStation::new("Brisbane", 21.4),
-        WeatherStation::new("Brussels", 10.5),
-        WeatherStation::new("Bucharest", 10.8),
-        WeatherStation::new("Budapest", 11.3),
-        WeatherStation::new("Bujumbura", 23.8),
-        WeatherStation::new("Bulawayo", 18.9),
-        WeatherStation::new("Burnie", 13.1),
-        WeatherStation::new("Busan", 15.0),
-        WeatherStation::new("Cabo San Lucas", 23.9),
-        WeatherStation::new("Cairns", 25.0),
-        WeatherStation::new("Cairo", 21.4),
-        WeatherStation::new("Calgary", 4.4),
-        WeatherStation::new("Canberra", 13.1),
-        WeatherStation::new("Cape Town", 16.2),
-        WeatherStation::new("Changsha", 17.4),
-        WeatherStation::new("Charlotte", 16.1),
-        WeatherStation::new("Chiang Mai", 25.8),
-        WeatherStation::new("Chicago", 9.8),
-        WeatherStation::new("Chihuahua", 18.6),
-        WeatherStation::new("Chișinău", 10.2),
-        WeatherStation::new("Chittagong", 25.9),
-        WeatherStation::new("Chongqing", 18.6),
-        WeatherStation::new("Christchurch", 12.2),
-        WeatherStation::new("City of San Marino", 11.8),
-        WeatherStation::new("Colombo", 27.4),
-        WeatherStation::new("Columbus", 11.7),
-        WeatherStation::new("Conakry", 26.4),
-        WeatherStation::new("Copenhagen", 9.1),
-        WeatherStation::new("Cotonou", 27.2),
-        WeatherStation::new("Cracow", 9.3),
-        WeatherStation::new("Da Lat", 17.9),
-        WeatherStation::new("Da Nang", 25.8),
-        WeatherStation::new("Dakar", 24.0),
-        WeatherStation::new("Dallas", 19.0),
-        WeatherStation::new("Damascus", 17.0),
-        WeatherStation::new("Dampier", 26.4),
-        WeatherStation::new("Dar es Salaam", 25.8),
-        WeatherStation::new("Darwin", 27.6),
-        WeatherStation::new("Denpasar", 23.7),
-        WeatherStation::new("Denver", 10.4),
-        WeatherStation::new("Detroit", 10.0),
-        WeatherStation::new("Dhaka", 25.9),
-        WeatherStation::new("Dikson", -11.1),
-        WeatherStation::new("Dili", 26.6),
-        WeatherStation::new("Djibouti", 29.9),
-        WeatherStation::new("Dodoma", 22.7),
-        WeatherStation::new("Dolisie", 24.0),
-        WeatherStation::new("Douala", 26.7),
-        WeatherStation::new("Dubai", 26.9),
-        WeatherStation::new("Dublin", 9.8),
-        WeatherStation::new("Dunedin", 11.1),
-        WeatherStation::new("Durban", 20.6),
-        WeatherStation::new("Dushanbe", 14.7),
-        WeatherStation::new("Edinburgh", 9.3),
-        WeatherStation::new("Edmonton", 4.2),
-        WeatherStation::new("El Paso", 18.1),
-        WeatherStation::new("Entebbe", 21.0),
-        WeatherStation::new("Erbil", 19.5),
-        WeatherStation::new("Erzurum", 5.1),
-        WeatherStation::new("Fairbanks", -2.3),
-        WeatherStation::new("Fianarantsoa", 17.9),
-        WeatherStation::new("Flores, Petén", 26.4),
-        WeatherStation::new("Frankfurt", 10.6),
-        WeatherStation::new("Fresno", 17.9),
-        WeatherStation::new("Fukuoka", 17.0),
-        WeatherStation::new("Gabès", 19.5),
-        WeatherStation::new("Gaborone", 21.0),
-        WeatherStation::new("Gagnoa", 26.0),
-        WeatherStation::new("Gangtok", 15.2),
-        WeatherStation::new("Garissa", 29.3),
-        WeatherStation::new("Garoua", 28.3),
-        WeatherStation::new("George Town", 27.9),
-        WeatherStation::new("Ghanzi", 21.4),
-        WeatherStation::new("Gjoa Haven", -14.4),
-        WeatherStation::new("Guadalajara", 20.9),
-        WeatherStation::new("Guangzhou", 22.4),
-        WeatherStation::new("Guatemala City", 20.4),
-        WeatherStation::new("Halifax", 7.5),
-        WeatherStation::new("Hamburg", 9.7),
-        WeatherStation::new("Hamilton", 13.8),
-        WeatherStation::new("Hanga Roa", 20.5),
-        WeatherStation::new("Hanoi", 23.6),
-        WeatherStation::new("Harare", 18.4),
-        WeatherStation::new("Harbin", 5.0),
-        WeatherStation::new("Hargeisa", 21.7),
-        WeatherStation::new("Hat Yai", 27.0),
-        WeatherStation::new("Havana", 25.2),
-        WeatherStation::new("Helsinki", 5.9),
-        WeatherStation::new("Heraklion", 18.9),
-        WeatherStation::new("Hiroshima", 16.3),
-        WeatherStation::new("Ho Chi Minh City", 27.4),
-        WeatherStation::new("Hobart", 12.7),
-        WeatherStation::new("Hong Kong", 23.3),
-        WeatherStation::new("Honiara", 26.5),
-        WeatherStation::new("Honolulu", 25.4),
-        WeatherStation::new("Houston", 20.8),
-        WeatherStation::new("Ifrane", 11.4),
-        WeatherStation::new("Indianapolis", 11.8),
-        WeatherStation::new("Iqaluit", -9.3),
-        WeatherStation::new("Irkutsk", 1.0),
-        WeatherStation::new("Istanbul", 13.9),
-        WeatherStation::new("İzmir", 17.9),
-        WeatherStation::new("Jacksonville", 20.3),
-        WeatherStation::new("Jakarta", 26.7),
-        WeatherStation::new("Jayapura", 27.0),
-        WeatherStation::new("Jerusalem", 18.3),
-        WeatherStation::new("Johannesburg", 15.5),
-        WeatherStation::new("Jos", 22.8),
-        WeatherStation::new("Juba", 27.8),
-        WeatherStation::new("Kabul", 12.1),
-        WeatherStation::new("Kampala", 20.0),
-        WeatherStation::new("Kandi", 27.7),
-        WeatherStation::new("Kankan", 26.5),
-        WeatherStation::new("Kano", 26.4),
-        WeatherStation::new("Kansas City", 12.5),
-        WeatherStation::new("Karachi", 26.0),
-        WeatherStation::new("Karonga", 24.4),
-        WeatherStation::new("Kathmandu", 18.3),
-        WeatherStation::new("Khartoum", 29.9),
-        WeatherStation::new("Kingston", 27.4),
-        WeatherStation::new("Kinshasa", 25.3),
-        WeatherStation::new("Kolkata", 26.7),
-        WeatherStation::new("Kuala Lumpur", 27.3),
-        WeatherStation::new("Kumasi", 26.0),
-        WeatherStation::new("Kunming", 15.7),
-        WeatherStation::new("Kuopio", 3.4),
-        WeatherStation::new("Kuwait City", 25.7),
-        WeatherStation::new("Kyiv", 8.4),
-        WeatherStation::new("Kyoto", 15.8),
-        WeatherStation::new("La Ceiba", 26.2),
-        WeatherStation::new("La Paz", 23.7),
-        WeatherStation::new("Lagos", 26.8),
-        WeatherStation::new("Lahore", 24.3),
-        WeatherStation::new("Lake Havasu City", 23.7),
-        WeatherStation::new("Lake Tekapo", 8.7),
-        WeatherStation::new("Las Palmas de Gran Canaria", 21.2),
-        WeatherStation::new("Las Vegas", 20.3),
-        WeatherStation::new("Launceston", 13.1),
-        WeatherStation::new("Lhasa", 7.6),
-        WeatherStation::new("Libreville", 25.9),
-        WeatherStation::new("Lisbon", 17.5),
-        WeatherStation::new("Livingstone", 21.8),
-        WeatherStation::new("Ljubljana", 10.9),
-        WeatherStation::new("Lodwar", 29.3),
-        WeatherStation::new("Lomé", 26.9),
-        WeatherStation::new("London", 11.3),
-        WeatherStation::new("Los Angeles", 18.6),
-        WeatherStation::new("Louisville", 13.9),
-        WeatherStation::new("Luanda", 25.8),
-        WeatherStation::new("Lubumbashi", 20.8),
-        WeatherStation::new("Lusaka", 19.9),
-        WeatherStation::new("Luxembourg City", 9.3),
-        WeatherStation::new("Lviv", 7.8),
-        WeatherStation::new("Lyon", 12.5),
-        WeatherStation::new("Madrid", 15.0),
-        WeatherStation::new("Mahajanga", 26.3),
-        WeatherStation::new("Makassar", 26.7),
-        WeatherStation::new("Makurdi", 26.0),
-        WeatherStation::new("Malabo", 26.3),
-        WeatherStation::new("Malé", 28.0),
-        WeatherStation::new("Managua", 27.3),
-        WeatherStation::new("Manama", 26.5),
-        WeatherStation::new("Mandalay", 28.0),
-        WeatherStation::new("Mango", 28.1),
-        WeatherStation::new("Manila", 28.4),
-        WeatherStation::new("Maputo", 22.8),
-        WeatherStation::new("Marrakesh", 19.6),
-        WeatherStation::new("Marseille", 15.8),
-        WeatherStation::new("Maun", 22.4),
-        WeatherStation::new("Medan", 26.5),
-        WeatherStation::new("Mek'ele", 22.7),
-        WeatherStation::new("Melbourne", 15.1),
-        WeatherStation::new("Memphis", 17.2),
-        WeatherStation::new("Mexicali", 23.1),
-        WeatherStation::new("Mexico City", 17.5),
-        WeatherStation::new("Miami", 24.9),
-        WeatherStation::new("Milan", 13.0),
-        WeatherStation::new("Milwaukee", 8.9),
-        WeatherStation::new("Minneapolis", 7.8),
-        WeatherStation::new("Minsk", 6.7),
-        WeatherStation::new("Mogadishu", 27.1),
-        WeatherStation::new("Mombasa", 26.3),
-        WeatherStation::new("Monaco", 16.4),
-        WeatherStation::new("Moncton", 6.1),
-        WeatherStation::new("Monterrey", 22.3),
-        WeatherStation::new("Montreal", 6.8),
-        WeatherStation::new("Moscow", 5.8),
-        WeatherStation::new("Mumbai", 27.1),
-        WeatherStation::new("Murmansk", 0.6),
-        WeatherStation::new("Muscat", 28.0),
-        WeatherStation::new("Mzuzu", 17.7),
-        WeatherStation::new("N'Djamena", 28.3),
-        WeatherStation::new("Naha", 23.1),
-        WeatherStation::new("Nairobi", 17.8),
-        WeatherStation::new("Nakhon Ratchasima", 27.3),
-        WeatherStation::new("Napier", 14.6),
-        WeatherStation::new("Napoli", 15.9),
-        WeatherStation::new("Nashville", 15.4),
-        WeatherStation::new("Nassau", 24.6),
-        WeatherStation::new("Ndola", 20.3),
-        WeatherStation::new("New Delhi", 25.0),
-        WeatherStation::new("New Orleans", 20.7),
-        WeatherStation::new("New York City", 12.9),
-        WeatherStation::new("Ngaoundéré", 22.0),
-        WeatherStation::new("Niamey", 29.3),
-        WeatherStation::new("Nicosia", 19.7),
-        WeatherStation::new("Niigata", 13.9),
-        WeatherStation::new("Nouadhibou", 21.3),
-        WeatherStation::new("Nouakchott", 25.7),
-        WeatherStation::new("Novosibirsk", 1.7),
-        WeatherStation::new("Nuuk", -1.4),
-        WeatherStation::new("Odesa", 10.7),
-        WeatherStation::new("Odienné", 26.0),
-        WeatherStation::new("Oklahoma City", 15.9),
-        WeatherStation::new("Omaha", 10.6),
-        WeatherStation::new("Oranjestad", 28.1),
-        WeatherStation::new("Oslo", 5.7),
-        WeatherStation::new("Ottawa", 6.6),
-        WeatherStation::new("Ouagadougou", 28.3),
-        WeatherStation::new("Ouahigouya", 28.6),
-        WeatherStation::new("Ouarzazate", 18.9),
-        WeatherStation::new("Oulu", 2.7),
-        WeatherStation::new("Palembang", 27.3),
-        WeatherStation::new("Palermo", 18.5),
-        WeatherStation::new("Palm Springs", 24.5),
-        WeatherStation::new("Palmerston North", 13.2),
-        WeatherStation::new("Panama City", 28.0),
-        WeatherStation::new("Parakou", 26.8),
-        WeatherStation::new("Paris", 12.3),
-        WeatherStation::new("Perth", 18.7),
-        WeatherStation::new("Petropavlovsk-Kamchatsky", 1.9),
-        WeatherStation::new("Philadelphia", 13.2),
-        WeatherStation::new("Phnom Penh", 28.3),
-        WeatherStation::new("Phoenix", 23.9),
-        WeatherStation::new("Pittsburgh", 10.8),
-        WeatherStation::new("Podgorica", 15.3),
-        WeatherStation::new("Pointe-Noire", 26.1),
-        WeatherStation::new("Pontianak", 27.7),
-        WeatherStation::new("Port Moresby", 26.9),
-        WeatherStation::new("Port Sudan", 28.4),
-        WeatherStation::new("Port Vila", 24.3),
-        WeatherStation::new("Port-Gentil", 26.0),
-        WeatherStation::new("Portland (OR)", 12.4),
-        WeatherStation::new("Porto", 15.7),
-        WeatherStation::new("Prague", 8.4),
-        WeatherStation::new("Praia", 24.4),
-        WeatherStation::new("Pretoria", 18.2),
-        WeatherStation::new("Pyongyang", 10.8),
-        WeatherStation::new("Rabat", 17.2),
-        WeatherStation::new("Rangpur", 24.4),
-        WeatherStation::new("Reggane", 28.3),
-        WeatherStation::new("Reykjavík", 4.3),
-        WeatherStation::new("Riga", 6.2),
-        WeatherStation::new("Riyadh", 26.0),
-        WeatherStation::new("Rome", 15.2),
-        WeatherStation::new("Roseau", 26.2),
-        WeatherStation::new("Rostov-on-Don", 9.9),
-        WeatherStation::new("Sacramento", 16.3),
-        WeatherStation::new("Saint Petersburg", 5.8),
-        WeatherStation::new("Saint-Pierre", 5.7),
-        WeatherStation::new("Salt Lake City", 11.6),
-        WeatherStation::new("San Antonio", 20.8),
-        WeatherStation::new("San Diego", 17.8),
-        WeatherStation::new("San Francisco", 14.6),
-        WeatherStation::new("San Jose", 16.4),
-        WeatherStation::new("San José", 22.6),
-        WeatherStation::new("San Juan", 27.2),
-        WeatherStation::new("San Salvador", 23.1),
-        WeatherStation::new("Sana'a", 20.0),
-        WeatherStation::new("Santo Domingo", 25.9),
-        WeatherStation::new("Sapporo", 8.9),
-        WeatherStation::new("Sarajevo", 10.1),
-        WeatherStation::new("Saskatoon", 3.3),
-        WeatherStation::new("Seattle", 11.3),
-        WeatherStation::new("Ségou", 28.0),
-        WeatherStation::new("Seoul", 12.5),
-        WeatherStation::new("Seville", 19.2),
-        WeatherStation::new("Shanghai", 16.7),
-        WeatherStation::new("Singapore", 27.0),
-        WeatherStation::new("Skopje", 12.4),
-        WeatherStation::new("Sochi", 14.2),
-        WeatherStation::new("Sofia", 10.6),
-        WeatherStation::new("Sokoto", 28.0),
-        WeatherStation::new("Split", 16.1),
-        WeatherStation::new("St. John's", 5.0),
-        WeatherStation::new("St. Louis", 13.9),
-        WeatherStation::new("Stockholm", 6.6),
-        WeatherStation::new("Surabaya", 27.1),
-        WeatherStation::new("Suva", 25.6),
-        WeatherStation::new("Suwałki", 7.2),
-        WeatherStation::new("Sydney", 17.7),
-        WeatherStation::new("Tabora", 23.0),
-        WeatherStation::new("Tabriz", 12.6),
-        WeatherStation::new("Taipei", 23.0),
-        WeatherStation::new("Tallinn", 6.4),
-        WeatherStation::new("Tamale", 27.9),
-        WeatherStation::new("Tamanrasset", 21.7),
-        WeatherStation::new("Tampa", 22.9),
-        WeatherStation::new("Tashkent", 14.8),
-        WeatherStation::new("Tauranga", 14.8),
-        WeatherStation::new("Tbilisi", 12.9),
-        WeatherStation::new("Tegucigalpa", 21.7),
-        WeatherStation::new("Tehran", 17.0),
-        WeatherStation::new("Tel Aviv", 20.0),
-        WeatherStation::new("Thessaloniki", 16.0),
-        WeatherStation::new("Thiès", 24.0),
-        WeatherStation::new("Tijuana", 17.8),
-        WeatherStation::new("Timbuktu", 28.0),
-        WeatherStation::new("Tirana", 15.2),
-        WeatherStation::new("Toamasina", 23.4),
-        WeatherStation::new("Tokyo", 15.4),
-        WeatherStation::new("Toliara", 24.1),
-        WeatherStation::new("Toluca", 12.4),
-        WeatherStation::new("Toronto", 9.4),
-        WeatherStation::new("Tripoli", 20.0),
-        WeatherStation::new("Tromsø", 2.9),
-        WeatherStation::new("Tucson", 20.9),
-        WeatherStation::new("Tunis", 18.4),
-        WeatherStation::new("Ulaanbaatar", -0.4),
-        WeatherStation::new("Upington", 20.4),
-        WeatherStation::new("Ürümqi", 7.4),
-        WeatherStation::new("Vaduz", 10.1),
-        WeatherStation::new("Valencia", 18.3),
-        WeatherStation::new("Valletta", 18.8),
-        WeatherStation::new("Vancouver", 10.4),
-        WeatherStation::new("Veracruz", 25.4),
-        WeatherStation::new("Vienna", 10.4),
-        WeatherStation::new("Vientiane", 25.9),
-        WeatherStation::new("Villahermosa", 27.1),
-        WeatherStation::new("Vilnius", 6.0),
-        WeatherStation::new("Virginia Beach", 15.8),
-        WeatherStation::new("Vladivostok", 4.9),
-        WeatherStation::new("Warsaw", 8.5),
-        WeatherStation::new("Washington, D.C.", 14.6),
-        WeatherStation::new("Wau", 27.8),
-        WeatherStation::new("Wellington", 12.9),
-        WeatherStation::new("Whitehorse", -0.1),
-        WeatherStation::new("Wichita", 13.9),
-        WeatherStation::new("Willemstad", 28.0),
-        WeatherStation::new("Winnipeg", 3.0),
-        WeatherStation::new("Wrocław", 9.6),
-        WeatherStation::new("Xi'an", 14.1),
-        WeatherStation::new("Yakutsk", -8.8),
-        WeatherStation::new("Yangon", 27.5),
-        WeatherStation::new("Yaoundé", 23.8),
-        WeatherStation::new("Yellowknife", -4.3),
-        WeatherStation::new("Yerevan", 12.4),
-        WeatherStation::new("Yinchuan", 9.0),
-        WeatherStation::new("Zagreb", 10.7),
-        WeatherStation::new("Zanzibar City", 26.0),
-        WeatherStation::new("Zürich", 9.3),
-    ]
-}
+    fn levels(&self) -> RangeInclusive<i32> {
+        match self {
+            Compress::Gzip => 0..=9,
+            Compress::Zstd => 1..=22,
+        }
+    }
 
-#[derive(Parser)]
-#[command()]
-struct Cli {
-    #[arg(short, long, value_parser = clap::value_parser!(usize), default_value_t = 1_000_000_000_usize)]
-    size: usize,
+    fn default_level(&self) -> i32 {
+        match self {
+            Compress::Gzip => 6,
+            Compress::Zstd => 3,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        match self {
+            Compress::Gzip => cfg!(feature = "gzip"),
+            Compress::Zstd => cfg!(feature = "zstd"),
+        }
+    }
 }
 
 fn main() {
     let cli: Cli = Cli::parse();
     let now: Instant = Instant::now();
-    let weather_stations: Vec<WeatherStation> = get_weather_stations();
-    let path: String = format!("measurements_{}.txt", cli.size);
+    let mut generator: MeasurementsGenerator = match cli.stations {
+        Some(stations) => {
+            // Leave room for the random draws of unique names.
+            if name_capacity(cli.name_length.clone()) < 2 * stations as u128 {
+                Cli::command()
+                    .error(
+                        ErrorKind::ValueValidation,
+                        format!(
+                            "--name-length {}..{} cannot hold {} unique names, use longer names or fewer --stations",
+                            cli.name_length.start(),
+                            cli.name_length.end(),
+                            stations
+                        ),
+                    )
+                    .exit();
+            }
+            MeasurementsGenerator::with_synthetic_weather_stations(
+                stations as usize,
+                cli.name_length.clone(),
+                cli.distribution,
+                cli.seed,
+            )
+        }
+        None => match &cli.stations_file {
+            Some(stations_file) => MeasurementsGenerator::with_weather_stations(
+                read_stations_file(stations_file, cli.distribution),
+                cli.seed,
+            ),
+            None => MeasurementsGenerator::with_weather_stations(
+                get_weather_stations()
+                    .into_iter()
+                    .map(|station: WeatherStation| {
+                        let mean: f64 = station.mean();
+                        WeatherStation::with_distribution(station.name, mean, cli.distribution)
+                    })
+                    .collect(),
+                cli.seed,
+            ),
+        },
+    };
+    let weights: Option<Vec<f64>> = match (&cli.station_distribution, &cli.weights_file) {
+        (StationDistribution::WeightsFromFile, Some(weights_file)) => Some(read_weights_file(
+            weights_file,
+            generator.weather_stations(),
+        )),
+        (distribution, _) => distribution.weights(generator.weather_stations().len()),
+    };
+    if let Some(weights) = weights {
+        generator = generator.with_station_weights(&weights);
+    }
+    let corruption_kinds: &[Corruption] = match cli.corruption_kinds.as_slice() {
+        [] => Corruption::value_variants(),
+        kinds => kinds,
+    };
+    if let Some(corrupt_rate) = cli.corrupt_rate {
+        generator = generator.with_corruptions(Corruptions::new(corrupt_rate, corruption_kinds));
+    }
+    let threads: usize = cli
+        .threads
+        .unwrap_or_else(|| available_parallelism().map(NonZeroUsize::get).unwrap_or(1));
+    let level: Option<i32> = cli.compress.map(|compress: Compress| {
+        let level: i32 = cli.level.unwrap_or(compress.default_level());
+        if !compress.is_enabled() {
+            eprintln!(
+                "--compress {} requires the `{}` feature",
+                compress.name(),
+                compress.name()
+            );
+            exit(1);
+        }
+        if !compress.levels().contains(&level) {
+            eprintln!(
+                "{} is not a {} level within {:?}",
+                level,
+                compress.name(),
+                compress.levels()
+            );
+            exit(1);
+        }
+        level
+    });
+    let path: String = match cli.compress {
+        Some(compress) => format!("measurements_{}.txt.{}", cli.size, compress.extension()),
+        None => format!("measurements_{}.txt", cli.size),
+    };
     let file: File = File::create(&path).unwrap();
     let mut writer: BufWriter<File> = BufWriter::new(file);
 
-    for _ in 0..cli.size {
-        let station: &WeatherStation = weather_stations.choose(&mut rand::thread_rng()).unwrap();
-        writeln!(writer, "{};{}", station.name, station.get_measurement()).unwrap();
+    match (cli.compress, level) {
+        #[cfg(feature = "gzip")]
+        (Some(Compress::Gzip), Some(level)) => {
+            let mut encoder: GzEncoder<&mut BufWriter<File>> =
+                GzEncoder::new(&mut writer, flate2::Compression::new(level as u32));
+            write_measurements(&generator, &cli, threads, &path, &mut encoder);
+            encoder.finish().unwrap();
+        }
+        #[cfg(feature = "zstd")]
+        (Some(Compress::Zstd), Some(level)) => {
+            let mut encoder: ZstdFrameWriter<&mut BufWriter<File>> =
+                ZstdFrameWriter::new(&mut writer, level, cli.frame_rows, threads as u32);
+            write_measurements(&generator, &cli, threads, &path, &mut encoder);
+            encoder.finish().unwrap();
+        }
+        _ => write_measurements(&generator, &cli, threads, &path, &mut writer),
     }
+    writer.flush().unwrap();
     let duration: Duration = now.elapsed();
-    println!("{} generated in {:?}", &path, duration);
+    println!(
+        "{} generated in {:?} ({:.0} rows/s)",
+        path,
+        duration,
+        cli.size as f64 / duration.as_secs_f64()
+    );
+
+    let metadata_path: String = format!("measurements_{}.meta", cli.size);
+    write_metadata(
+        &metadata_path,
+        &path,
+        &cli,
+        &generator,
+        corruption_kinds,
+        level,
+    )
+    .unwrap();
+}
+
+/// Write the measurements, and the manifest of their defects if they are corrupted.
+fn write_measurements<W: Write>(
+    generator: &MeasurementsGenerator,
+    cli: &Cli,
+    threads: usize,
+    path: &str,
+    writer: &mut W,
+) {
+    match cli.corrupt_rate {
+        Some(_) => {
+            let manifest_path: String = format!("measurements_{}.manifest", cli.size);
+            let mut manifest: BufWriter<File> =
+                BufWriter::new(File::create(manifest_path).unwrap());
+            writeln!(manifest, "# Defects of {}, as line;kind", path).unwrap();
+            generator
+                .write_measurements_with_defects(cli.size, threads, writer, |defect: Defect| {
+                    writeln!(manifest, "{};{}", defect.line, defect.corruption.name())
+                })
+                .unwrap();
+            manifest.flush().unwrap();
+        }
+        None => generator
+            .write_measurements_parallel(cli.size, threads, writer)
+            .unwrap(),
+    }
+}
+
+/// Write how the measurements were generated, as `key=value` lines, to reproduce them.
+fn write_metadata(
+    metadata_path: &str,
+    path: &str,
+    cli: &Cli,
+    generator: &MeasurementsGenerator,
+    corruption_kinds: &[Corruption],
+    level: Option<i32>,
+) -> std::io::Result<()> {
+    let mut writer: BufWriter<File> = BufWriter::new(File::create(metadata_path)?);
+    let stations_source: String = match (cli.stations, &cli.stations_file) {
+        (Some(stations), _) => format!(
+            "synthetic:{}:{}..{}",
+            stations,
+            cli.name_length.start(),
+            cli.name_length.end()
+        ),
+        (None, Some(stations_file)) => format!("file:{}", stations_file),
+        (None, None) => String::from("built-in"),
+    };
+
+    writeln!(writer, "# Metadata of {}", path)?;
+    writeln!(writer, "size={}", cli.size)?;
+    writeln!(writer, "seed={}", generator.seed())?;
+    writeln!(writer, "stations={}", generator.weather_stations().len())?;
+    writeln!(writer, "stations_source={}", stations_source)?;
+    writeln!(writer, "temperature_distribution={}", cli.distribution)?;
+    writeln!(writer, "station_distribution={}", cli.station_distribution)?;
+    if let Some(weights_file) = &cli.weights_file {
+        writeln!(writer, "weights_file={}", weights_file)?;
+    }
+    if let Some(corrupt_rate) = cli.corrupt_rate {
+        let kinds: Vec<&str> = corruption_kinds.iter().map(Corruption::name).collect();
+        writeln!(writer, "corrupt_rate={}", corrupt_rate)?;
+        writeln!(writer, "corruption_kinds={}", kinds.join(","))?;
+    }
+    if let (Some(compress), Some(level)) = (cli.compress, level) {
+        writeln!(writer, "compression={}", compress.name())?;
+        writeln!(writer, "compression_level={}", level)?;
+        if compress == Compress::Zstd {
+            writeln!(writer, "frame_rows={}", cli.frame_rows)?;
+        }
+    }
+    writer.flush()
+}
+
+fn read_stations_file(path: &str, distribution: TemperatureDistribution) -> Vec<WeatherStation> {
+    File::open(path)
+        .map_err(ObrcError::from)
+        .and_then(|file: File| read_weather_stations(BufReader::new(file), distribution))
+        .unwrap_or_else(|error: ObrcError| {
+            eprintln!("Cannot read weather stations from {}: {}", path, error);
+            exit(1);
+        })
+}
+
+fn read_weights_file(path: &str, stations: &[WeatherStation]) -> Vec<f64> {
+    File::open(path)
+        .map_err(ObrcError::from)
+        .and_then(|file: File| read_station_weights(BufReader::new(file), stations))
+        .unwrap_or_else(|error: ObrcError| {
+            eprintln!("Cannot read station weights from {}: {}", path, error);
+            exit(1);
+        })
+}
+
+fn parse_name_length(value: &str) -> Result<RangeInclusive<usize>, String> {
+    let bounds: Option<(usize, usize)> = value
+        .split_once("..")
+        .and_then(|(min, max)| Some((min.parse().ok()?, max.parse().ok()?)));
+    match bounds {
+        Some((min, max)) if 1 <= min && min <= max && max <= MAX_NAME_LENGTH => Ok(min..=max),
+        _ => Err(format!(
+            "{:?} is not a `min..max` range within 1 and {} bytes",
+            value, MAX_NAME_LENGTH
+        )),
+    }
+}
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!("{:?} is not a rate between 0 and 1", value)),
+    }
 }
@@ -0,0 +1,117 @@
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Temperatures that are not `-?\d?\d(\.\d)?`.
+const INVALID_NUMBERS: [&str; 7] = ["", "abc", "1.2.3", "--1.0", "12,5", "100.0", "NaN"];
+
+/// Defect injected into a measurement line, as found in real-world feeds.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum Corruption {
+    /// `\r\n` line ending.
+    Crlf,
+    /// Empty line, inserted before a measurement.
+    BlankLine,
+    /// No `;` between the station name and the temperature.
+    MissingSeparator,
+    /// Unparsable temperature, e.g. `abc`, `12,5` or `100.0`.
+    InvalidNumber,
+    /// Space or tab after the temperature.
+    TrailingWhitespace,
+    /// `0xFF` byte in the station name.
+    InvalidUtf8,
+    /// No `\n` after the last line of the file.
+    MissingFinalNewline,
+}
+
+impl Corruption {
+    /// Name of the defect, as accepted by `--corruption-kinds`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Corruption::Crlf => "crlf",
+            Corruption::BlankLine => "blank-line",
+            Corruption::MissingSeparator => "missing-separator",
+            Corruption::InvalidNumber => "invalid-number",
+            Corruption::TrailingWhitespace => "trailing-whitespace",
+            Corruption::InvalidUtf8 => "invalid-utf8",
+            Corruption::MissingFinalNewline => "missing-final-newline",
+        }
+    }
+}
+
+/// Defect injected at a line of the output, numbered from 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Defect {
+    pub line: u64,
+    pub corruption: Corruption,
+}
+
+/// Which defects to inject into the measurements, and how often.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corruptions {
+    rate: f64,
+    /// Kinds of defects of single lines, all but [`Corruption::MissingFinalNewline`].
+    line_kinds: Vec<Corruption>,
+    missing_final_newline: bool,
+}
+
+impl Corruptions {
+    /// Give every measurement a probability of `rate` to get one of the given defects,
+    /// except [`Corruption::MissingFinalNewline`], which applies to the last line whatever the rate.
+    ///
+    /// # Panics
+    /// If the rate is not within 0 and 1.
+    pub fn new(rate: f64, kinds: &[Corruption]) -> Corruptions {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "Corruption rate {} is not within 0 and 1",
+            rate
+        );
+        Corruptions {
+            rate,
+            line_kinds: kinds
+                .iter()
+                .copied()
+                .filter(|kind: &Corruption| *kind != Corruption::MissingFinalNewline)
+                .collect(),
+            missing_final_newline: kinds.contains(&Corruption::MissingFinalNewline),
+        }
+    }
+
+    pub(crate) fn missing_final_newline(&self) -> bool {
+        self.missing_final_newline
+    }
+
+    /// Maybe corrupt the measurement line at the end of the buffer, from `start`,
+    /// with a name of `name_len` bytes, and return the injected defect.
+    pub(crate) fn corrupt<R: Rng + ?Sized>(
+        &self,
+        buffer: &mut Vec<u8>,
+        start: usize,
+        name_len: usize,
+        rng: &mut R,
+    ) -> Option<Corruption> {
+        if self.line_kinds.is_empty() || !rng.gen_bool(self.rate) {
+            return None;
+        }
+
+        let kind: Corruption = *self.line_kinds.choose(rng).unwrap();
+        let end: usize = buffer.len() - 1;
+        match kind {
+            Corruption::Crlf => buffer.insert(end, b'\r'),
+            Corruption::BlankLine => buffer.insert(start, b'\n'),
+            Corruption::MissingSeparator => {
+                buffer.remove(start + name_len);
+            }
+            Corruption::InvalidNumber => {
+                buffer.truncate(start + name_len + 1);
+                buffer.extend_from_slice(INVALID_NUMBERS.choose(rng).unwrap().as_bytes());
+                buffer.push(b'\n');
+            }
+            Corruption::TrailingWhitespace => buffer.insert(end, *b" \t".choose(rng).unwrap()),
+            Corruption::InvalidUtf8 => buffer.insert(start + rng.gen_range(0..=name_len), 0xFF),
+            Corruption::MissingFinalNewline => unreachable!(),
+        }
+        Some(kind)
+    }
+}
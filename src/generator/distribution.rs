@@ -0,0 +1,360 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use std::str::FromStr;
+
+use rand::Rng;
+use rand_distr::Distribution;
+use rand_distr::Uniform;
+
+/// Standard deviation of the default normal distribution, in degrees.
+pub const STD_DEV: f64 = 10.0;
+
+/// Distribution of the temperatures of a weather station around its mean, in degrees.
+///
+/// Written and parsed as `normal:<σ>`, `uniform:<half width>`, `bimodal:<offset>:<σ>`
+/// or `student-t:<degrees of freedom>:<scale>`; a plain number is a normal distribution's σ.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TemperatureDistribution {
+    /// Normal distribution with this standard deviation.
+    Normal { std_dev: f64 },
+    /// Uniform distribution within this distance of the mean.
+    Uniform { half_width: f64 },
+    /// Seasonal mix of two normal distributions, half of the time `offset` below the mean
+    /// and half of the time above it.
+    Bimodal { offset: f64, std_dev: f64 },
+    /// Heavy-tailed Student's t-distribution, scaled.
+    StudentT { degrees_of_freedom: f64, scale: f64 },
+}
+
+impl Default for TemperatureDistribution {
+    fn default() -> Self {
+        TemperatureDistribution::Normal { std_dev: STD_DEV }
+    }
+}
+
+impl Display for TemperatureDistribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            TemperatureDistribution::Normal { std_dev } => write!(f, "normal:{}", std_dev),
+            TemperatureDistribution::Uniform { half_width } => write!(f, "uniform:{}", half_width),
+            TemperatureDistribution::Bimodal { offset, std_dev } => {
+                write!(f, "bimodal:{}:{}", offset, std_dev)
+            }
+            TemperatureDistribution::StudentT {
+                degrees_of_freedom,
+                scale,
+            } => write!(f, "student-t:{}:{}", degrees_of_freedom, scale),
+        }
+    }
+}
+
+impl FromStr for TemperatureDistribution {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts: std::str::Split<'_, char> = value.split(':');
+        let kind: &str = parts.next().unwrap_or_default();
+        let parameters: Option<Vec<f64>> = parts
+            .map(|part: &str| part.parse::<f64>().ok().filter(|p: &f64| p.is_finite()))
+            .collect();
+        let invalid = || format!("{:?} is not a valid temperature distribution", value);
+        let non_negative = |parameter: f64| -> std::result::Result<f64, String> {
+            if parameter >= 0.0 {
+                Ok(parameter)
+            } else {
+                Err(invalid())
+            }
+        };
+
+        if let Ok(std_dev) = kind.parse::<f64>() {
+            if std_dev.is_finite() && value == kind {
+                return Ok(TemperatureDistribution::Normal {
+                    std_dev: non_negative(std_dev)?,
+                });
+            }
+        }
+        match (kind, parameters.ok_or_else(invalid)?.as_slice()) {
+            ("normal", [std_dev]) => Ok(TemperatureDistribution::Normal {
+                std_dev: non_negative(*std_dev)?,
+            }),
+            ("uniform", [half_width]) => Ok(TemperatureDistribution::Uniform {
+                half_width: non_negative(*half_width)?,
+            }),
+            ("bimodal", [offset, std_dev]) => Ok(TemperatureDistribution::Bimodal {
+                offset: *offset,
+                std_dev: non_negative(*std_dev)?,
+            }),
+            ("student-t", [degrees_of_freedom, scale]) if *degrees_of_freedom > 0.0 => {
+                Ok(TemperatureDistribution::StudentT {
+                    degrees_of_freedom: *degrees_of_freedom,
+                    scale: non_negative(*scale)?,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Distribution of the measurements among the weather stations.
+///
+/// Written and parsed as `uniform`, `zipf:<exponent>` or `weights-from-file`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StationDistribution {
+    /// Every station is as frequent.
+    Uniform,
+    /// The station of rank `k`, in the order of the list, is `1 / k^exponent` times as frequent as the first.
+    Zipf { exponent: f64 },
+    /// Frequencies proportional to weights read from a file, see
+    /// [`read_station_weights`](super::weather_stations::read_station_weights).
+    WeightsFromFile,
+}
+
+impl StationDistribution {
+    /// Weights of the given number of stations, or `None` if uniform or read from a file.
+    pub fn weights(&self, count: usize) -> Option<Vec<f64>> {
+        match self {
+            StationDistribution::Zipf { exponent } => Some(
+                (1..=count)
+                    .map(|rank: usize| libm::pow(rank as f64, -exponent))
+                    .collect(),
+            ),
+            StationDistribution::Uniform | StationDistribution::WeightsFromFile => None,
+        }
+    }
+}
+
+impl Display for StationDistribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            StationDistribution::Uniform => write!(f, "uniform"),
+            StationDistribution::Zipf { exponent } => write!(f, "zipf:{}", exponent),
+            StationDistribution::WeightsFromFile => write!(f, "weights-from-file"),
+        }
+    }
+}
+
+impl FromStr for StationDistribution {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.split_once(':') {
+            None if value == "uniform" => Ok(StationDistribution::Uniform),
+            None if value == "weights-from-file" => Ok(StationDistribution::WeightsFromFile),
+            Some(("zipf", exponent)) => match exponent.parse::<f64>() {
+                Ok(exponent) if exponent.is_finite() && exponent >= 0.0 => {
+                    Ok(StationDistribution::Zipf { exponent })
+                }
+                _ => Err(format!("{:?} is not a valid station distribution", value)),
+            },
+            _ => Err(format!("{:?} is not a valid station distribution", value)),
+        }
+    }
+}
+
+/// Sampler of a [`TemperatureDistribution`] around a mean.
+///
+/// Only uses IEEE 754 arithmetic and the pure-Rust `libm`, rather than the platform's `exp`, `ln` or `pow`,
+/// so that a seed gives the same samples on every platform.
+#[derive(Debug)]
+pub(crate) enum Sampler {
+    Normal(f64, f64),
+    Uniform(Uniform<f64>),
+    Bimodal(f64, f64, f64),
+    StudentT(f64, f64, f64),
+}
+
+impl Sampler {
+    pub(crate) fn new(mean: f64, distribution: TemperatureDistribution) -> Sampler {
+        match distribution {
+            TemperatureDistribution::Normal { std_dev } => Sampler::Normal(mean, std_dev),
+            TemperatureDistribution::Uniform { half_width } => {
+                Sampler::Uniform(Uniform::new_inclusive(mean - half_width, mean + half_width))
+            }
+            TemperatureDistribution::Bimodal { offset, std_dev } => {
+                Sampler::Bimodal(mean, offset, std_dev)
+            }
+            TemperatureDistribution::StudentT {
+                degrees_of_freedom,
+                scale,
+            } => Sampler::StudentT(mean, scale, degrees_of_freedom),
+        }
+    }
+}
+
+impl Distribution<f64> for Sampler {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            Sampler::Normal(mean, std_dev) => mean + std_dev * standard_normal(rng),
+            Sampler::Uniform(uniform) => uniform.sample(rng),
+            Sampler::Bimodal(mean, offset, std_dev) => match rng.gen::<bool>() {
+                true => mean + offset + std_dev * standard_normal(rng),
+                false => mean - offset + std_dev * standard_normal(rng),
+            },
+            Sampler::StudentT(mean, scale, degrees_of_freedom) => {
+                mean + scale * student_t(rng, *degrees_of_freedom)
+            }
+        }
+    }
+}
+
+/// A point drawn uniformly from the unit disc without its center, and its squared distance to it.
+fn unit_disc<R: Rng + ?Sized>(rng: &mut R) -> (f64, f64) {
+    loop {
+        let u: f64 = rng.gen_range(-1.0..1.0);
+        let v: f64 = rng.gen_range(-1.0..1.0);
+        let w: f64 = u * u + v * v;
+        if w > 0.0 && w < 1.0 {
+            return (u, w);
+        }
+    }
+}
+
+/// Standard normal sample, with Marsaglia's polar method.
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let (u, w) = unit_disc(rng);
+    u * (-2.0 * libm::log(w) / w).sqrt()
+}
+
+/// Sample of Student's t-distribution, with Bailey's polar method.
+fn student_t<R: Rng + ?Sized>(rng: &mut R, degrees_of_freedom: f64) -> f64 {
+    let (u, w) = unit_disc(rng);
+    u * (degrees_of_freedom * (libm::pow(w, -2.0 / degrees_of_freedom) - 1.0) / w).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use rand_distr::Distribution;
+
+    use super::Sampler;
+    use super::StationDistribution;
+    use super::TemperatureDistribution;
+
+    #[test]
+    fn test_parse() {
+        for (value, expected) in [
+            ("5", TemperatureDistribution::Normal { std_dev: 5.0 }),
+            (
+                "normal:2.5",
+                TemperatureDistribution::Normal { std_dev: 2.5 },
+            ),
+            (
+                "uniform:20",
+                TemperatureDistribution::Uniform { half_width: 20.0 },
+            ),
+            (
+                "bimodal:-15:3",
+                TemperatureDistribution::Bimodal {
+                    offset: -15.0,
+                    std_dev: 3.0,
+                },
+            ),
+            (
+                "student-t:3:4",
+                TemperatureDistribution::StudentT {
+                    degrees_of_freedom: 3.0,
+                    scale: 4.0,
+                },
+            ),
+        ] {
+            assert_eq!(Ok(expected), value.parse::<TemperatureDistribution>());
+            assert_eq!(Ok(expected), expected.to_string().parse());
+        }
+
+        for value in [
+            "",
+            "-1",
+            "NaN",
+            "inf",
+            "normal",
+            "normal:-1",
+            "normal:1:2",
+            "uniform:x",
+            "bimodal:1",
+            "student-t:0:1",
+            "student-t:1:-1",
+            "cauchy:1",
+        ] {
+            assert_eq!(
+                Err(format!(
+                    "{:?} is not a valid temperature distribution",
+                    value
+                )),
+                value.parse::<TemperatureDistribution>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_station_distribution() {
+        for (value, expected) in [
+            ("uniform", StationDistribution::Uniform),
+            ("zipf:1.5", StationDistribution::Zipf { exponent: 1.5 }),
+            ("weights-from-file", StationDistribution::WeightsFromFile),
+        ] {
+            assert_eq!(Ok(expected), value.parse::<StationDistribution>());
+            assert_eq!(value, expected.to_string());
+        }
+
+        for value in [
+            "",
+            "zipf",
+            "zipf:",
+            "zipf:-1",
+            "zipf:inf",
+            "uniform:1",
+            "weights",
+        ] {
+            assert_eq!(
+                Err(format!("{:?} is not a valid station distribution", value)),
+                value.parse::<StationDistribution>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_zipf_weights() {
+        assert_eq!(
+            Some(vec![1.0, 0.25, 1.0 / 9.0]),
+            StationDistribution::Zipf { exponent: 2.0 }.weights(3)
+        );
+        assert_eq!(None, StationDistribution::Uniform.weights(3));
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(42);
+        let samples = |distribution: TemperatureDistribution, rng: &mut ChaCha8Rng| -> Vec<f64> {
+            let sampler: Sampler = Sampler::new(10.0, distribution);
+            (0..10_000).map(|_| sampler.sample(rng)).collect()
+        };
+
+        let uniform: Vec<f64> = samples(
+            TemperatureDistribution::Uniform { half_width: 5.0 },
+            &mut rng,
+        );
+        assert!(uniform.iter().all(|t: &f64| (5.0..=15.0).contains(t)));
+
+        let bimodal: Vec<f64> = samples(
+            TemperatureDistribution::Bimodal {
+                offset: 20.0,
+                std_dev: 1.0,
+            },
+            &mut rng,
+        );
+        assert!(bimodal.iter().all(|t: &f64| (t - 10.0).abs() > 10.0));
+        assert!(bimodal.iter().filter(|t: &&f64| **t > 10.0).count() > 4_000);
+        assert!(bimodal.iter().filter(|t: &&f64| **t < 10.0).count() > 4_000);
+
+        let student_t: Vec<f64> = samples(
+            TemperatureDistribution::StudentT {
+                degrees_of_freedom: 1.0,
+                scale: 1.0,
+            },
+            &mut rng,
+        );
+        assert!(student_t.iter().any(|t: &f64| (t - 10.0).abs() > 100.0));
+    }
+}
@@ -0,0 +1,520 @@
+use std::io;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::sync::mpsc::sync_channel;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::SyncSender;
+use std::thread::scope;
+
+use rand::distributions::WeightedIndex;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rand_distr::Distribution;
+
+use super::corruption::Corruption;
+use super::corruption::Corruptions;
+use super::corruption::Defect;
+use super::distribution::TemperatureDistribution;
+use super::weather_stations::get_weather_stations;
+use super::weather_stations::synthesize_weather_stations;
+use super::weather_stations::WeatherStation;
+
+/// Number of measurements generated at once, from their own random number stream.
+pub const BATCH_ROWS: usize = 1 << 16;
+
+/// Stream of the seed the synthetic weather stations are drawn from, apart from the batches' ones.
+const STATIONS_STREAM: u64 = u64::MAX;
+
+/// First stream of the seed the defects are drawn from, one per batch,
+/// so that the measurements themselves do not depend on the corruptions.
+const CORRUPTION_STREAMS: u64 = 1 << 63;
+
+/// Batches a thread may generate ahead of the writer.
+const BATCHES_AHEAD: usize = 2;
+
+/// Generates `<station name>;<temperature>` measurements, one per line.
+///
+/// The measurements are generated in batches of [`BATCH_ROWS`], each from its own ChaCha8 stream
+/// of the seed, so they only depend on the seed and the size, and not on the number of threads.
+/// ChaCha8 produces the same streams on every platform, and the samples only go through
+/// IEEE 754 arithmetic, the pure-Rust `libm` and decimal rounding, never the platform's `libm`,
+/// so the output is byte-for-byte reproducible across runs and platforms.
+pub struct MeasurementsGenerator {
+    weather_stations: Vec<WeatherStation>,
+    seed: u64,
+    /// Distribution of the measurements among the stations, uniform if `None`.
+    station_index: Option<WeightedIndex<f64>>,
+    corruptions: Option<Corruptions>,
+}
+
+impl MeasurementsGenerator {
+    /// Generator over the built-in weather stations,
+    /// reproducible if a seed is given and seeded from the OS otherwise.
+    pub fn new(seed: Option<u64>) -> MeasurementsGenerator {
+        MeasurementsGenerator::with_weather_stations(get_weather_stations(), seed)
+    }
+
+    /// Generator over the given weather stations,
+    /// reproducible if a seed is given and seeded from the OS otherwise.
+    pub fn with_weather_stations(
+        weather_stations: Vec<WeatherStation>,
+        seed: Option<u64>,
+    ) -> MeasurementsGenerator {
+        MeasurementsGenerator {
+            weather_stations,
+            seed: seed.unwrap_or_else(|| ChaCha8Rng::from_entropy().gen()),
+            station_index: None,
+            corruptions: None,
+        }
+    }
+
+    /// Inject defects into the measurements, drawn from the seed as well.
+    pub fn with_corruptions(mut self, corruptions: Corruptions) -> MeasurementsGenerator {
+        self.corruptions = Some(corruptions);
+        self
+    }
+
+    /// Pick the stations in proportion to these weights, one per station, instead of uniformly.
+    ///
+    /// # Panics
+    /// If there is not one weight per station, or no weight is positive.
+    pub fn with_station_weights(mut self, weights: &[f64]) -> MeasurementsGenerator {
+        assert_eq!(
+            self.weather_stations.len(),
+            weights.len(),
+            "Expected one weight per station"
+        );
+        self.station_index = Some(WeightedIndex::new(weights).unwrap());
+        self
+    }
+
+    /// Seed of the measurements, drawn from the OS if none was given.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn weather_stations(&self) -> &[WeatherStation] {
+        &self.weather_stations
+    }
+
+    /// Generator over `count` synthesized weather stations with names of `name_length` bytes
+    /// and the given distribution, see [`synthesize_weather_stations`], drawn from the seed as well.
+    pub fn with_synthetic_weather_stations(
+        count: usize,
+        name_length: RangeInclusive<usize>,
+        distribution: TemperatureDistribution,
+        seed: Option<u64>,
+    ) -> MeasurementsGenerator {
+        let seed: u64 = seed.unwrap_or_else(|| ChaCha8Rng::from_entropy().gen());
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(STATIONS_STREAM);
+
+        MeasurementsGenerator::with_weather_stations(
+            synthesize_weather_stations(count, name_length, distribution, &mut rng),
+            Some(seed),
+        )
+    }
+
+    /// Write `size` measurements.
+    pub fn write_measurements<W: Write>(&self, size: usize, writer: &mut W) -> io::Result<()> {
+        self.write_measurements_parallel(size, 1, writer)
+    }
+
+    /// Write `size` measurements, generated on `threads` threads.
+    ///
+    /// Batches are assigned to the threads in turn and written in order,
+    /// so the output is the same as [`MeasurementsGenerator::write_measurements`].
+    pub fn write_measurements_parallel<W: Write>(
+        &self,
+        size: usize,
+        threads: usize,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        self.write_measurements_with_defects(size, threads, writer, |_: Defect| Ok(()))
+    }
+
+    /// Write `size` measurements, generated on `threads` threads,
+    /// and report the injected defects in order, see [`MeasurementsGenerator::with_corruptions`].
+    pub fn write_measurements_with_defects<W: Write, F: FnMut(Defect) -> io::Result<()>>(
+        &self,
+        size: usize,
+        threads: usize,
+        writer: &mut W,
+        mut on_defect: F,
+    ) -> io::Result<()> {
+        let batches: usize = size.div_ceil(BATCH_ROWS);
+        let threads: usize = threads.clamp(1, batches.max(1));
+
+        scope(|s| {
+            let receivers: Vec<Receiver<Batch>> = (0..threads)
+                .map(|thread: usize| {
+                    let (sender, receiver): (SyncSender<Batch>, Receiver<Batch>) =
+                        sync_channel(BATCHES_AHEAD);
+                    s.spawn(move || {
+                        for batch in (thread..batches).step_by(threads) {
+                            // The writer failed and stopped receiving.
+                            if sender.send(self.generate_batch(batch, size)).is_err() {
+                                return;
+                            }
+                        }
+                    });
+                    receiver
+                })
+                .collect();
+
+            let mut lines: u64 = 0;
+            for batch in 0..batches {
+                let batch: Batch = receivers[batch % threads].recv().unwrap();
+                writer.write_all(&batch.bytes)?;
+                for (line, corruption) in batch.defects {
+                    on_defect(Defect {
+                        line: lines + line + 1,
+                        corruption,
+                    })?;
+                }
+                lines += batch.lines;
+            }
+            Ok(())
+        })
+    }
+
+    /// Generate `size` measurements in memory, e.g. as a test fixture.
+    ///
+    /// # Panics
+    /// If [`Corruption::InvalidUtf8`] defects are injected.
+    pub fn generate(&self, size: usize) -> String {
+        let mut output: Vec<u8> = Vec::new();
+        self.write_measurements(size, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Generate the measurements of the given batch, out of `size` measurements.
+    fn generate_batch(&self, batch: usize, size: usize) -> Batch {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(batch as u64);
+        let mut corruption_rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(self.seed);
+        corruption_rng.set_stream(CORRUPTION_STREAMS + batch as u64);
+        let rows: usize = size.min((batch + 1) * BATCH_ROWS) - batch * BATCH_ROWS;
+        let mut output: Batch = Batch {
+            bytes: Vec::new(),
+            lines: rows as u64,
+            defects: Vec::new(),
+        };
+
+        for row in 0..rows {
+            let station: &WeatherStation = match &self.station_index {
+                Some(station_index) => &self.weather_stations[station_index.sample(&mut rng)],
+                None => self.weather_stations.choose(&mut rng).unwrap(),
+            };
+            let start: usize = output.bytes.len();
+            writeln!(
+                output.bytes,
+                "{};{}",
+                station.name,
+                station.get_measurement(&mut rng)
+            )
+            .unwrap();
+
+            let Some(corruptions) = &self.corruptions else {
+                continue;
+            };
+            let line: u64 = row as u64 + output.lines - rows as u64;
+            match corruptions.corrupt(
+                &mut output.bytes,
+                start,
+                station.name.len(),
+                &mut corruption_rng,
+            ) {
+                Some(Corruption::BlankLine) => {
+                    output.defects.push((line, Corruption::BlankLine));
+                    output.lines += 1;
+                }
+                Some(corruption) => output.defects.push((line, corruption)),
+                None => {}
+            }
+        }
+
+        if let Some(corruptions) = &self.corruptions {
+            if corruptions.missing_final_newline() && batch * BATCH_ROWS + rows == size {
+                output.bytes.pop();
+                output
+                    .defects
+                    .push((output.lines - 1, Corruption::MissingFinalNewline));
+            }
+        }
+        output
+    }
+}
+
+/// Measurements of a batch, and the defects injected into them by line, numbered from 0.
+struct Batch {
+    bytes: Vec<u8>,
+    lines: u64,
+    defects: Vec<(u64, Corruption)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ValueEnum;
+
+    use super::Corruption;
+    use super::Corruptions;
+    use super::Defect;
+    use super::MeasurementsGenerator;
+    use super::TemperatureDistribution;
+    use super::BATCH_ROWS;
+    use crate::generator::distribution::StationDistribution;
+    use crate::solutions::aggregation::Aggregation;
+    use crate::solutions::error::ObrcError;
+    use crate::solutions::solution_streaming::SolutionStreaming;
+    use crate::utils::parsing::parse_temperature;
+
+    #[test]
+    fn test_generate_is_reproducible() {
+        let expected: String = MeasurementsGenerator::new(Some(42)).generate(10_000);
+
+        assert_eq!(
+            expected,
+            MeasurementsGenerator::new(Some(42)).generate(10_000)
+        );
+        assert_ne!(
+            expected,
+            MeasurementsGenerator::new(Some(43)).generate(10_000)
+        );
+    }
+
+    #[test]
+    fn test_generate_is_stable() {
+        assert_eq!(
+            "Da Lat;12.6\nKansas City;21.2\nNgaoundéré;17\n",
+            MeasurementsGenerator::new(Some(42)).generate(3)
+        );
+    }
+
+    #[test]
+    fn test_generate_golden_output() {
+        let fnv1a = |input: &str| -> u64 {
+            input
+                .bytes()
+                .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte: u8| {
+                    (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+                })
+        };
+
+        // Recorded once: any change breaks the reproduction of files generated with a seed.
+        for (distribution, expected_lines, expected_hash) in [
+            (
+                "normal:10",
+                "wpAKts🌡;11.1\ne;-17.6\n東l;19.1\n",
+                16062980941301551528,
+            ),
+            (
+                "uniform:20",
+                "wpAKts🌡;23.1\néf;26.1\n東l;-1.7\n",
+                3386173102055687768,
+            ),
+            (
+                "bimodal:15:3",
+                "wpAKts🌡;-6.3\n-sh-k;-17\nmSro yphü;1.7\n",
+                12719848163754045188,
+            ),
+            (
+                "student-t:3:4",
+                "wpAKts🌡;7.6\ne;-15\n東l;15.2\n",
+                10986247880651638915,
+            ),
+        ] {
+            let generator: MeasurementsGenerator =
+                MeasurementsGenerator::with_synthetic_weather_stations(
+                    100,
+                    1..=10,
+                    distribution.parse::<TemperatureDistribution>().unwrap(),
+                    Some(42),
+                );
+            let weights: Vec<f64> = StationDistribution::Zipf { exponent: 1.5 }
+                .weights(generator.weather_stations().len())
+                .unwrap();
+            let generator: MeasurementsGenerator = generator.with_station_weights(&weights);
+
+            assert_eq!(expected_lines, generator.generate(3), "{}", distribution);
+            assert_eq!(
+                expected_hash,
+                fnv1a(&generator.generate(100_000)),
+                "{}",
+                distribution
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_is_solvable() {
+        let input: String = MeasurementsGenerator::new(Some(42)).generate(10_000);
+
+        let aggregation: Aggregation = SolutionStreaming::solve_reader(input.as_bytes()).unwrap();
+
+        assert_eq!(
+            10_000_u64,
+            aggregation
+                .stations
+                .values()
+                .map(|stats| stats.count())
+                .sum()
+        );
+    }
+
+    #[test]
+    fn test_generate_synthetic() {
+        let input: String = MeasurementsGenerator::with_synthetic_weather_stations(
+            10_000,
+            1..=100,
+            TemperatureDistribution::default(),
+            Some(42),
+        )
+        .generate(100_000);
+
+        let aggregation: Aggregation = SolutionStreaming::solve_reader(input.as_bytes()).unwrap();
+
+        assert!(aggregation.stations.len() > 9_000);
+        assert_eq!(
+            input,
+            MeasurementsGenerator::with_synthetic_weather_stations(
+                10_000,
+                1..=100,
+                TemperatureDistribution::default(),
+                Some(42),
+            )
+            .generate(100_000)
+        );
+    }
+
+    #[test]
+    fn test_generate_with_station_weights() {
+        let generator: MeasurementsGenerator = MeasurementsGenerator::new(Some(42));
+        let weights: Vec<f64> = StationDistribution::Zipf { exponent: 1.0 }
+            .weights(generator.weather_stations().len())
+            .unwrap();
+        let input: String = generator.with_station_weights(&weights).generate(100_000);
+
+        let aggregation: Aggregation = SolutionStreaming::solve_reader(input.as_bytes()).unwrap();
+
+        let count = |name: &str| aggregation.stations[name].count();
+        assert!(count("Abha") > 3 * count("Abidjan") / 2);
+        assert!(count("Abidjan") > 50 * count("Zürich"));
+
+        let mut weights: Vec<f64> = vec![0.0; 413];
+        weights[1] = 1.0;
+        let input: String = MeasurementsGenerator::new(Some(42))
+            .with_station_weights(&weights)
+            .generate(1_000);
+        assert!(input.lines().all(|line: &str| line.starts_with("Abidjan;")));
+    }
+
+    #[test]
+    fn test_write_measurements_parallel() {
+        let generator: MeasurementsGenerator = MeasurementsGenerator::new(Some(42));
+        let size: usize = 3 * BATCH_ROWS + 123;
+        let expected: String = generator.generate(size);
+
+        for threads in [1, 2, 3, 8] {
+            let mut actual: Vec<u8> = Vec::new();
+            generator
+                .write_measurements_parallel(size, threads, &mut actual)
+                .unwrap();
+            assert_eq!(expected.as_bytes(), actual, "{} threads", threads);
+        }
+        assert_eq!(size, expected.lines().count());
+    }
+
+    fn corrupted(size: usize, threads: usize, corruptions: Corruptions) -> (Vec<u8>, Vec<Defect>) {
+        let generator: MeasurementsGenerator =
+            MeasurementsGenerator::new(Some(42)).with_corruptions(corruptions);
+        let mut output: Vec<u8> = Vec::new();
+        let mut defects: Vec<Defect> = Vec::new();
+        generator
+            .write_measurements_with_defects(size, threads, &mut output, |defect: Defect| {
+                defects.push(defect);
+                Ok(())
+            })
+            .unwrap();
+        (output, defects)
+    }
+
+    #[test]
+    fn test_write_measurements_with_defects() {
+        let size: usize = 2 * BATCH_ROWS + 123;
+        let corruptions: Corruptions = Corruptions::new(0.01, Corruption::value_variants());
+        let (output, defects) = corrupted(size, 1, corruptions.clone());
+        let lines: Vec<&[u8]> = output.split(|byte: &u8| *byte == b'\n').collect();
+
+        assert_eq!(
+            (output.clone(), defects.clone()),
+            corrupted(size, 3, corruptions)
+        );
+        assert!((1_000..2_000).contains(&defects.len()));
+        assert_eq!(
+            Some(&Defect {
+                line: lines.len() as u64,
+                corruption: Corruption::MissingFinalNewline
+            }),
+            defects.last()
+        );
+        for kind in Corruption::value_variants() {
+            assert!(defects
+                .iter()
+                .any(|defect: &Defect| defect.corruption == *kind));
+        }
+
+        let mut defects: std::slice::Iter<'_, Defect> = defects.iter();
+        let mut defect: Option<&Defect> = defects.next();
+        for (index, line) in lines.iter().enumerate() {
+            let valid = |line: &[u8]| -> bool {
+                let text: Option<&str> = std::str::from_utf8(line).ok();
+                text.and_then(|text: &str| text.split_once(';'))
+                    .is_some_and(|(_, temperature)| {
+                        parse_temperature(temperature.as_bytes()).is_some()
+                    })
+            };
+            let mut corruptions: Vec<Corruption> = Vec::new();
+            while let Some(current) = defect.filter(|d: &&Defect| d.line == index as u64 + 1) {
+                corruptions.push(current.corruption);
+                defect = defects.next();
+            }
+            match corruptions.first() {
+                None => assert!(valid(line), "line {}", index + 1),
+                Some(Corruption::Crlf) => assert!(line.ends_with(b"\r")),
+                Some(Corruption::BlankLine) => assert!(line.is_empty()),
+                Some(Corruption::MissingSeparator) => assert!(!line.contains(&b';')),
+                Some(Corruption::InvalidNumber) => assert!(!valid(line)),
+                Some(Corruption::TrailingWhitespace) => {
+                    assert!(line.ends_with(b" ") || line.ends_with(b"\t"))
+                }
+                Some(Corruption::InvalidUtf8) => assert!(std::str::from_utf8(line).is_err()),
+                Some(Corruption::MissingFinalNewline) => assert_eq!(lines.len(), index + 1),
+            }
+        }
+        assert_eq!(None, defect);
+    }
+
+    #[test]
+    fn test_corrupted_measurements_are_rejected() {
+        let (output, defects) = corrupted(
+            1_000,
+            1,
+            Corruptions::new(0.1, &[Corruption::MissingSeparator]),
+        );
+
+        assert!(!defects.is_empty());
+        assert!(matches!(
+            SolutionStreaming::solve_reader(output.as_slice()),
+            Err(ObrcError::MalformedLine { .. })
+        ));
+        let (output, defects) = corrupted(1_000, 1, Corruptions::new(0.1, &[Corruption::Crlf]));
+        assert!(!defects.is_empty());
+        assert_eq!(
+            MeasurementsGenerator::new(Some(42)).generate(1_000),
+            String::from_utf8(output).unwrap().replace('\r', "")
+        );
+    }
+}
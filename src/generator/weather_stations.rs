@@ -0,0 +1,967 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::ops::RangeInclusive;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_distr::Distribution;
+
+use super::distribution::Sampler;
+use super::distribution::TemperatureDistribution;
+use crate::solutions::error::ObrcError;
+
+/// Bound of the temperatures, in degrees, as measurements must be within [-99.9, 99.9].
+const MAX_TEMPERATURE: f64 = 99.9;
+
+/// Maximum length in bytes of a station name, as allowed by the challenge.
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Characters of synthetic station names: ASCII, and two, three and four-byte UTF-8 characters.
+const NAME_CHARS: [char; 32] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'k', 'l', 'm', 'n', 'o', 'p', 'r', 's', 't', 'u',
+    'v', 'w', 'y', 'A', 'K', 'S', ' ', '-', 'é', 'ü', 'ж', '東', '🌡',
+];
+
+/// Attempts per station to synthesize a name that is not taken yet.
+const NAME_ATTEMPTS: usize = 1_000;
+
+pub struct WeatherStation {
+    pub name: String,
+    mean: f64,
+    distribution: TemperatureDistribution,
+    sampler: Sampler,
+}
+
+impl WeatherStation {
+    pub fn new<S: Into<String>>(name: S, mean: f64) -> WeatherStation {
+        WeatherStation::with_distribution(name, mean, TemperatureDistribution::default())
+    }
+
+    /// # Panics
+    /// If the distribution has invalid parameters, e.g. a negative standard deviation.
+    pub fn with_distribution<S: Into<String>>(
+        name: S,
+        mean: f64,
+        distribution: TemperatureDistribution,
+    ) -> WeatherStation {
+        WeatherStation {
+            name: name.into(),
+            mean,
+            distribution,
+            sampler: Sampler::new(mean, distribution),
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn distribution(&self) -> TemperatureDistribution {
+        self.distribution
+    }
+
+    /// Sample a temperature, clamped into [-99.9, 99.9] and rounded to one decimal digit.
+    pub fn get_measurement<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let temperature: f64 = self
+            .sampler
+            .sample(rng)
+            .clamp(-MAX_TEMPERATURE, MAX_TEMPERATURE);
+        format!("{:.1$}", temperature, 1).parse::<f64>().unwrap()
+    }
+}
+
+pub fn get_weather_stations() -> Vec<WeatherStation> {
+    vec![
+        WeatherStation::new("Abha", 18.0),
+        WeatherStation::new("Abidjan", 26.0),
+        WeatherStation::new("Abéché", 29.4),
+        WeatherStation::new("Accra", 26.4),
+        WeatherStation::new("Addis Ababa", 16.0),
+        WeatherStation::new("Adelaide", 17.3),
+        WeatherStation::new("Aden", 29.1),
+        WeatherStation::new("Ahvaz", 25.4),
+        WeatherStation::new("Albuquerque", 14.0),
+        WeatherStation::new("Alexandra", 11.0),
+        WeatherStation::new("Alexandria", 20.0),
+        WeatherStation::new("Algiers", 18.2),
+        WeatherStation::new("Alice Springs", 21.0),
+        WeatherStation::new("Almaty", 10.0),
+        WeatherStation::new("Amsterdam", 10.2),
+        WeatherStation::new("Anadyr", -6.9),
+        WeatherStation::new("Anchorage", 2.8),
+        WeatherStation::new("Andorra la Vella", 9.8),
+        WeatherStation::new("Ankara", 12.0),
+        WeatherStation::new("Antananarivo", 17.9),
+        WeatherStation::new("Antsiranana", 25.2),
+        WeatherStation::new("Arkhangelsk", 1.3),
+        WeatherStation::new("Ashgabat", 17.1),
+        WeatherStation::new("Asmara", 15.6),
+        WeatherStation::new("Assab", 30.5),
+        WeatherStation::new("Astana", 3.5),
+        WeatherStation::new("Athens", 19.2),
+        WeatherStation::new("Atlanta", 17.0),
+        WeatherStation::new("Auckland", 15.2),
+        WeatherStation::new("Austin", 20.7),
+        WeatherStation::new("Baghdad", 22.77),
+        WeatherStation::new("Baguio", 19.5),
+        WeatherStation::new("Baku", 15.1),
+        WeatherStation::new("Baltimore", 13.1),
+        WeatherStation::new("Bamako", 27.8),
+        WeatherStation::new("Bangkok", 28.6),
+        WeatherStation::new("Bangui", 26.0),
+        WeatherStation::new("Banjul", 26.0),
+        WeatherStation::new("Barcelona", 18.2),
+        WeatherStation::new("Bata", 25.1),
+        WeatherStation::new("Batumi", 14.0),
+        WeatherStation::new("Beijing", 12.9),
+        WeatherStation::new("Beirut", 20.9),
+        WeatherStation::new("Belgrade", 12.5),
+        WeatherStation::new("Belize City", 26.7),
+        WeatherStation::new("Benghazi", 19.9),
+        WeatherStation::new("Bergen", 7.7),
+        WeatherStation::new("Berlin", 10.3),
+        WeatherStation::new("Bilbao", 14.7),
+        WeatherStation::new("Birao", 26.5),
+        WeatherStation::new("Bishkek", 11.3),
+        WeatherStation::new("Bissau", 27.0),
+        WeatherStation::new("Blantyre", 22.2),
+        WeatherStation::new("Bloemfontein", 15.6),
+        WeatherStation::new("Boise", 11.4),
+        WeatherStation::new("Bordeaux", 14.2),
+        WeatherStation::new("Bosaso", 30.0),
+        WeatherStation::new("Boston", 10.9),
+        WeatherStation::new("Bouaké", 26.0),
+        WeatherStation::new("Bratislava", 10.5),
+        WeatherStation::new("Brazzaville", 25.0),
+        WeatherStation::new("Bridgetown", 27.0),
+        WeatherStation::new("Brisbane", 21.4),
+        WeatherStation::new("Brussels", 10.5),
+        WeatherStation::new("Bucharest", 10.8),
+        WeatherStation::new("Budapest", 11.3),
+        WeatherStation::new("Bujumbura", 23.8),
+        WeatherStation::new("Bulawayo", 18.9),
+        WeatherStation::new("Burnie", 13.1),
+        WeatherStation::new("Busan", 15.0),
+        WeatherStation::new("Cabo San Lucas", 23.9),
+        WeatherStation::new("Cairns", 25.0),
+        WeatherStation::new("Cairo", 21.4),
+        WeatherStation::new("Calgary", 4.4),
+        WeatherStation::new("Canberra", 13.1),
+        WeatherStation::new("Cape Town", 16.2),
+        WeatherStation::new("Changsha", 17.4),
+        WeatherStation::new("Charlotte", 16.1),
+        WeatherStation::new("Chiang Mai", 25.8),
+        WeatherStation::new("Chicago", 9.8),
+        WeatherStation::new("Chihuahua", 18.6),
+        WeatherStation::new("Chișinău", 10.2),
+        WeatherStation::new("Chittagong", 25.9),
+        WeatherStation::new("Chongqing", 18.6),
+        WeatherStation::new("Christchurch", 12.2),
+        WeatherStation::new("City of San Marino", 11.8),
+        WeatherStation::new("Colombo", 27.4),
+        WeatherStation::new("Columbus", 11.7),
+        WeatherStation::new("Conakry", 26.4),
+        WeatherStation::new("Copenhagen", 9.1),
+        WeatherStation::new("Cotonou", 27.2),
+        WeatherStation::new("Cracow", 9.3),
+        WeatherStation::new("Da Lat", 17.9),
+        WeatherStation::new("Da Nang", 25.8),
+        WeatherStation::new("Dakar", 24.0),
+        WeatherStation::new("Dallas", 19.0),
+        WeatherStation::new("Damascus", 17.0),
+        WeatherStation::new("Dampier", 26.4),
+        WeatherStation::new("Dar es Salaam", 25.8),
+        WeatherStation::new("Darwin", 27.6),
+        WeatherStation::new("Denpasar", 23.7),
+        WeatherStation::new("Denver", 10.4),
+        WeatherStation::new("Detroit", 10.0),
+        WeatherStation::new("Dhaka", 25.9),
+        WeatherStation::new("Dikson", -11.1),
+        WeatherStation::new("Dili", 26.6),
+        WeatherStation::new("Djibouti", 29.9),
+        WeatherStation::new("Dodoma", 22.7),
+        WeatherStation::new("Dolisie", 24.0),
+        WeatherStation::new("Douala", 26.7),
+        WeatherStation::new("Dubai", 26.9),
+        WeatherStation::new("Dublin", 9.8),
+        WeatherStation::new("Dunedin", 11.1),
+        WeatherStation::new("Durban", 20.6),
+        WeatherStation::new("Dushanbe", 14.7),
+        WeatherStation::new("Edinburgh", 9.3),
+        WeatherStation::new("Edmonton", 4.2),
+        WeatherStation::new("El Paso", 18.1),
+        WeatherStation::new("Entebbe", 21.0),
+        WeatherStation::new("Erbil", 19.5),
+        WeatherStation::new("Erzurum", 5.1),
+        WeatherStation::new("Fairbanks", -2.3),
+        WeatherStation::new("Fianarantsoa", 17.9),
+        WeatherStation::new("Flores, Petén", 26.4),
+        WeatherStation::new("Frankfurt", 10.6),
+        WeatherStation::new("Fresno", 17.9),
+        WeatherStation::new("Fukuoka", 17.0),
+        WeatherStation::new("Gabès", 19.5),
+        WeatherStation::new("Gaborone", 21.0),
+        WeatherStation::new("Gagnoa", 26.0),
+        WeatherStation::new("Gangtok", 15.2),
+        WeatherStation::new("Garissa", 29.3),
+        WeatherStation::new("Garoua", 28.3),
+        WeatherStation::new("George Town", 27.9),
+        WeatherStation::new("Ghanzi", 21.4),
+        WeatherStation::new("Gjoa Haven", -14.4),
+        WeatherStation::new("Guadalajara", 20.9),
+        WeatherStation::new("Guangzhou", 22.4),
+        WeatherStation::new("Guatemala City", 20.4),
+        WeatherStation::new("Halifax", 7.5),
+        WeatherStation::new("Hamburg", 9.7),
+        WeatherStation::new("Hamilton", 13.8),
+        WeatherStation::new("Hanga Roa", 20.5),
+        WeatherStation::new("Hanoi", 23.6),
+        WeatherStation::new("Harare", 18.4),
+        WeatherStation::new("Harbin", 5.0),
+        WeatherStation::new("Hargeisa", 21.7),
+        WeatherStation::new("Hat Yai", 27.0),
+        WeatherStation::new("Havana", 25.2),
+        WeatherStation::new("Helsinki", 5.9),
+        WeatherStation::new("Heraklion", 18.9),
+        WeatherStation::new("Hiroshima", 16.3),
+        WeatherStation::new("Ho Chi Minh City", 27.4),
+        WeatherStation::new("Hobart", 12.7),
+        WeatherStation::new("Hong Kong", 23.3),
+        WeatherStation::new("Honiara", 26.5),
+        WeatherStation::new("Honolulu", 25.4),
+        WeatherStation::new("Houston", 20.8),
+        WeatherStation::new("Ifrane", 11.4),
+        WeatherStation::new("Indianapolis", 11.8),
+        WeatherStation::new("Iqaluit", -9.3),
+        WeatherStation::new("Irkutsk", 1.0),
+        WeatherStation::new("Istanbul", 13.9),
+        WeatherStation::new("İzmir", 17.9),
+        WeatherStation::new("Jacksonville", 20.3),
+        WeatherStation::new("Jakarta", 26.7),
+        WeatherStation::new("Jayapura", 27.0),
+        WeatherStation::new("Jerusalem", 18.3),
+        WeatherStation::new("Johannesburg", 15.5),
+        WeatherStation::new("Jos", 22.8),
+        WeatherStation::new("Juba", 27.8),
+        WeatherStation::new("Kabul", 12.1),
+        WeatherStation::new("Kampala", 20.0),
+        WeatherStation::new("Kandi", 27.7),
+        WeatherStation::new("Kankan", 26.5),
+        WeatherStation::new("Kano", 26.4),
+        WeatherStation::new("Kansas City", 12.5),
+        WeatherStation::new("Karachi", 26.0),
+        WeatherStation::new("Karonga", 24.4),
+        WeatherStation::new("Kathmandu", 18.3),
+        WeatherStation::new("Khartoum", 29.9),
+        WeatherStation::new("Kingston", 27.4),
+        WeatherStation::new("Kinshasa", 25.3),
+        WeatherStation::new("Kolkata", 26.7),
+        WeatherStation::new("Kuala Lumpur", 27.3),
+        WeatherStation::new("Kumasi", 26.0),
+        WeatherStation::new("Kunming", 15.7),
+        WeatherStation::new("Kuopio", 3.4),
+        WeatherStation::new("Kuwait City", 25.7),
+        WeatherStation::new("Kyiv", 8.4),
+        WeatherStation::new("Kyoto", 15.8),
+        WeatherStation::new("La Ceiba", 26.2),
+        WeatherStation::new("La Paz", 23.7),
+        WeatherStation::new("Lagos", 26.8),
+        WeatherStation::new("Lahore", 24.3),
+        WeatherStation::new("Lake Havasu City", 23.7),
+        WeatherStation::new("Lake Tekapo", 8.7),
+        WeatherStation::new("Las Palmas de Gran Canaria", 21.2),
+        WeatherStation::new("Las Vegas", 20.3),
+        WeatherStation::new("Launceston", 13.1),
+        WeatherStation::new("Lhasa", 7.6),
+        WeatherStation::new("Libreville", 25.9),
+        WeatherStation::new("Lisbon", 17.5),
+        WeatherStation::new("Livingstone", 21.8),
+        WeatherStation::new("Ljubljana", 10.9),
+        WeatherStation::new("Lodwar", 29.3),
+        WeatherStation::new("Lomé", 26.9),
+        WeatherStation::new("London", 11.3),
+        WeatherStation::new("Los Angeles", 18.6),
+        WeatherStation::new("Louisville", 13.9),
+        WeatherStation::new("Luanda", 25.8),
+        WeatherStation::new("Lubumbashi", 20.8),
+        WeatherStation::new("Lusaka", 19.9),
+        WeatherStation::new("Luxembourg City", 9.3),
+        WeatherStation::new("Lviv", 7.8),
+        WeatherStation::new("Lyon", 12.5),
+        WeatherStation::new("Madrid", 15.0),
+        WeatherStation::new("Mahajanga", 26.3),
+        WeatherStation::new("Makassar", 26.7),
+        WeatherStation::new("Makurdi", 26.0),
+        WeatherStation::new("Malabo", 26.3),
+        WeatherStation::new("Malé", 28.0),
+        WeatherStation::new("Managua", 27.3),
+        WeatherStation::new("Manama", 26.5),
+        WeatherStation::new("Mandalay", 28.0),
+        WeatherStation::new("Mango", 28.1),
+        WeatherStation::new("Manila", 28.4),
+        WeatherStation::new("Maputo", 22.8),
+        WeatherStation::new("Marrakesh", 19.6),
+        WeatherStation::new("Marseille", 15.8),
+        WeatherStation::new("Maun", 22.4),
+        WeatherStation::new("Medan", 26.5),
+        WeatherStation::new("Mek'ele", 22.7),
+        WeatherStation::new("Melbourne", 15.1),
+        WeatherStation::new("Memphis", 17.2),
+        WeatherStation::new("Mexicali", 23.1),
+        WeatherStation::new("Mexico City", 17.5),
+        WeatherStation::new("Miami", 24.9),
+        WeatherStation::new("Milan", 13.0),
+        WeatherStation::new("Milwaukee", 8.9),
+        WeatherStation::new("Minneapolis", 7.8),
+        WeatherStation::new("Minsk", 6.7),
+        WeatherStation::new("Mogadishu", 27.1),
+        WeatherStation::new("Mombasa", 26.3),
+        WeatherStation::new("Monaco", 16.4),
+        WeatherStation::new("Moncton", 6.1),
+        WeatherStation::new("Monterrey", 22.3),
+        WeatherStation::new("Montreal", 6.8),
+        WeatherStation::new("Moscow", 5.8),
+        WeatherStation::new("Mumbai", 27.1),
+        WeatherStation::new("Murmansk", 0.6),
+        WeatherStation::new("Muscat", 28.0),
+        WeatherStation::new("Mzuzu", 17.7),
+        WeatherStation::new("N'Djamena", 28.3),
+        WeatherStation::new("Naha", 23.1),
+        WeatherStation::new("Nairobi", 17.8),
+        WeatherStation::new("Nakhon Ratchasima", 27.3),
+        WeatherStation::new("Napier", 14.6),
+        WeatherStation::new("Napoli", 15.9),
+        WeatherStation::new("Nashville", 15.4),
+        WeatherStation::new("Nassau", 24.6),
+        WeatherStation::new("Ndola", 20.3),
+        WeatherStation::new("New Delhi", 25.0),
+        WeatherStation::new("New Orleans", 20.7),
+        WeatherStation::new("New York City", 12.9),
+        WeatherStation::new("Ngaoundéré", 22.0),
+        WeatherStation::new("Niamey", 29.3),
+        WeatherStation::new("Nicosia", 19.7),
+        WeatherStation::new("Niigata", 13.9),
+        WeatherStation::new("Nouadhibou", 21.3),
+        WeatherStation::new("Nouakchott", 25.7),
+        WeatherStation::new("Novosibirsk", 1.7),
+        WeatherStation::new("Nuuk", -1.4),
+        WeatherStation::new("Odesa", 10.7),
+        WeatherStation::new("Odienné", 26.0),
+        WeatherStation::new("Oklahoma City", 15.9),
+        WeatherStation::new("Omaha", 10.6),
+        WeatherStation::new("Oranjestad", 28.1),
+        WeatherStation::new("Oslo", 5.7),
+        WeatherStation::new("Ottawa", 6.6),
+        WeatherStation::new("Ouagadougou", 28.3),
+        WeatherStation::new("Ouahigouya", 28.6),
+        WeatherStation::new("Ouarzazate", 18.9),
+        WeatherStation::new("Oulu", 2.7),
+        WeatherStation::new("Palembang", 27.3),
+        WeatherStation::new("Palermo", 18.5),
+        WeatherStation::new("Palm Springs", 24.5),
+        WeatherStation::new("Palmerston North", 13.2),
+        WeatherStation::new("Panama City", 28.0),
+        WeatherStation::new("Parakou", 26.8),
+        WeatherStation::new("Paris", 12.3),
+        WeatherStation::new("Perth", 18.7),
+        WeatherStation::new("Petropavlovsk-Kamchatsky", 1.9),
+        WeatherStation::new("Philadelphia", 13.2),
+        WeatherStation::new("Phnom Penh", 28.3),
+        WeatherStation::new("Phoenix", 23.9),
+        WeatherStation::new("Pittsburgh", 10.8),
+        WeatherStation::new("Podgorica", 15.3),
+        WeatherStation::new("Pointe-Noire", 26.1),
+        WeatherStation::new("Pontianak", 27.7),
+        WeatherStation::new("Port Moresby", 26.9),
+        WeatherStation::new("Port Sudan", 28.4),
+        WeatherStation::new("Port Vila", 24.3),
+        WeatherStation::new("Port-Gentil", 26.0),
+        WeatherStation::new("Portland (OR)", 12.4),
+        WeatherStation::new("Porto", 15.7),
+        WeatherStation::new("Prague", 8.4),
+        WeatherStation::new("Praia", 24.4),
+        WeatherStation::new("Pretoria", 18.2),
+        WeatherStation::new("Pyongyang", 10.8),
+        WeatherStation::new("Rabat", 17.2),
+        WeatherStation::new("Rangpur", 24.4),
+        WeatherStation::new("Reggane", 28.3),
+        WeatherStation::new("Reykjavík", 4.3),
+        WeatherStation::new("Riga", 6.2),
+        WeatherStation::new("Riyadh", 26.0),
+        WeatherStation::new("Rome", 15.2),
+        WeatherStation::new("Roseau", 26.2),
+        WeatherStation::new("Rostov-on-Don", 9.9),
+        WeatherStation::new("Sacramento", 16.3),
+        WeatherStation::new("Saint Petersburg", 5.8),
+        WeatherStation::new("Saint-Pierre", 5.7),
+        WeatherStation::new("Salt Lake City", 11.6),
+        WeatherStation::new("San Antonio", 20.8),
+        WeatherStation::new("San Diego", 17.8),
+        WeatherStation::new("San Francisco", 14.6),
+        WeatherStation::new("San Jose", 16.4),
+        WeatherStation::new("San José", 22.6),
+        WeatherStation::new("San Juan", 27.2),
+        WeatherStation::new("San Salvador", 23.1),
+        WeatherStation::new("Sana'a", 20.0),
+        WeatherStation::new("Santo Domingo", 25.9),
+        WeatherStation::new("Sapporo", 8.9),
+        WeatherStation::new("Sarajevo", 10.1),
+        WeatherStation::new("Saskatoon", 3.3),
+        WeatherStation::new("Seattle", 11.3),
+        WeatherStation::new("Ségou", 28.0),
+        WeatherStation::new("Seoul", 12.5),
+        WeatherStation::new("Seville", 19.2),
+        WeatherStation::new("Shanghai", 16.7),
+        WeatherStation::new("Singapore", 27.0),
+        WeatherStation::new("Skopje", 12.4),
+        WeatherStation::new("Sochi", 14.2),
+        WeatherStation::new("Sofia", 10.6),
+        WeatherStation::new("Sokoto", 28.0),
+        WeatherStation::new("Split", 16.1),
+        WeatherStation::new("St. John's", 5.0),
+        WeatherStation::new("St. Louis", 13.9),
+        WeatherStation::new("Stockholm", 6.6),
+        WeatherStation::new("Surabaya", 27.1),
+        WeatherStation::new("Suva", 25.6),
+        WeatherStation::new("Suwałki", 7.2),
+        WeatherStation::new("Sydney", 17.7),
+        WeatherStation::new("Tabora", 23.0),
+        WeatherStation::new("Tabriz", 12.6),
+        WeatherStation::new("Taipei", 23.0),
+        WeatherStation::new("Tallinn", 6.4),
+        WeatherStation::new("Tamale", 27.9),
+        WeatherStation::new("Tamanrasset", 21.7),
+        WeatherStation::new("Tampa", 22.9),
+        WeatherStation::new("Tashkent", 14.8),
+        WeatherStation::new("Tauranga", 14.8),
+        WeatherStation::new("Tbilisi", 12.9),
+        WeatherStation::new("Tegucigalpa", 21.7),
+        WeatherStation::new("Tehran", 17.0),
+        WeatherStation::new("Tel Aviv", 20.0),
+        WeatherStation::new("Thessaloniki", 16.0),
+        WeatherStation::new("Thiès", 24.0),
+        WeatherStation::new("Tijuana", 17.8),
+        WeatherStation::new("Timbuktu", 28.0),
+        WeatherStation::new("Tirana", 15.2),
+        WeatherStation::new("Toamasina", 23.4),
+        WeatherStation::new("Tokyo", 15.4),
+        WeatherStation::new("Toliara", 24.1),
+        WeatherStation::new("Toluca", 12.4),
+        WeatherStation::new("Toronto", 9.4),
+        WeatherStation::new("Tripoli", 20.0),
+        WeatherStation::new("Tromsø", 2.9),
+        WeatherStation::new("Tucson", 20.9),
+        WeatherStation::new("Tunis", 18.4),
+        WeatherStation::new("Ulaanbaatar", -0.4),
+        WeatherStation::new("Upington", 20.4),
+        WeatherStation::new("Ürümqi", 7.4),
+        WeatherStation::new("Vaduz", 10.1),
+        WeatherStation::new("Valencia", 18.3),
+        WeatherStation::new("Valletta", 18.8),
+        WeatherStation::new("Vancouver", 10.4),
+        WeatherStation::new("Veracruz", 25.4),
+        WeatherStation::new("Vienna", 10.4),
+        WeatherStation::new("Vientiane", 25.9),
+        WeatherStation::new("Villahermosa", 27.1),
+        WeatherStation::new("Vilnius", 6.0),
+        WeatherStation::new("Virginia Beach", 15.8),
+        WeatherStation::new("Vladivostok", 4.9),
+        WeatherStation::new("Warsaw", 8.5),
+        WeatherStation::new("Washington, D.C.", 14.6),
+        WeatherStation::new("Wau", 27.8),
+        WeatherStation::new("Wellington", 12.9),
+        WeatherStation::new("Whitehorse", -0.1),
+        WeatherStation::new("Wichita", 13.9),
+        WeatherStation::new("Willemstad", 28.0),
+        WeatherStation::new("Winnipeg", 3.0),
+        WeatherStation::new("Wrocław", 9.6),
+        WeatherStation::new("Xi'an", 14.1),
+        WeatherStation::new("Yakutsk", -8.8),
+        WeatherStation::new("Yangon", 27.5),
+        WeatherStation::new("Yaoundé", 23.8),
+        WeatherStation::new("Yellowknife", -4.3),
+        WeatherStation::new("Yerevan", 12.4),
+        WeatherStation::new("Yinchuan", 9.0),
+        WeatherStation::new("Zagreb", 10.7),
+        WeatherStation::new("Zanzibar City", 26.0),
+        WeatherStation::new("Zürich", 9.3),
+    ]
+}
+
+/// Read a catalog of weather stations, one `name;mean[;distribution]` per line, in degrees.
+/// The distribution is a [`TemperatureDistribution`], e.g. `uniform:15`, or a plain standard
+/// deviation; stations without one follow the given default distribution.
+/// Empty lines and lines starting with `#` are skipped.
+///
+/// Every invalid line is reported with its number: missing fields, names that are empty,
+/// over [`MAX_NAME_LENGTH`] bytes, duplicated or containing `;` or a newline character,
+/// means outside [-99.9, 99.9], and invalid distributions.
+pub fn read_weather_stations<R: BufRead>(
+    reader: R,
+    default_distribution: TemperatureDistribution,
+) -> Result<Vec<WeatherStation>, ObrcError> {
+    let mut stations: Vec<WeatherStation> = Vec::new();
+    let mut lines_by_name: HashMap<String, usize> = HashMap::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (index, line) in reader.split(b'\n').enumerate() {
+        let number: usize = index + 1;
+        let line: Vec<u8> = line?;
+        let line: &[u8] = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+
+        match parse_catalog_line(line) {
+            Ok((name, mean, distribution)) => match lines_by_name.get(&name) {
+                Some(first) => errors.push(format!(
+                    "line {}: duplicate name {:?}, first on line {}",
+                    number, name, first
+                )),
+                None => {
+                    lines_by_name.insert(name.clone(), number);
+                    stations.push(WeatherStation::with_distribution(
+                        name,
+                        mean,
+                        distribution.unwrap_or(default_distribution),
+                    ));
+                }
+            },
+            Err(reason) => errors.push(format!("line {}: {}", number, reason)),
+        }
+    }
+
+    if stations.is_empty() && errors.is_empty() {
+        errors.push(String::from("no weather stations"));
+    }
+    if errors.is_empty() {
+        Ok(stations)
+    } else {
+        Err(ObrcError::InvalidStationsFile(errors))
+    }
+}
+
+/// Read the weights of the given weather stations, one `name;weight` per line,
+/// for [`StationDistribution::WeightsFromFile`](super::distribution::StationDistribution).
+/// Stations missing from the file have no measurements.
+/// Empty lines and lines starting with `#` are skipped.
+///
+/// Every invalid line is reported with its number: missing fields, unknown or duplicated names,
+/// and weights that are negative or not finite. At least one weight must be positive.
+pub fn read_station_weights<R: BufRead>(
+    reader: R,
+    stations: &[WeatherStation],
+) -> Result<Vec<f64>, ObrcError> {
+    let indexes: HashMap<&str, usize> = stations
+        .iter()
+        .enumerate()
+        .map(|(index, station)| (station.name.as_str(), index))
+        .collect();
+    let mut weights: Vec<f64> = vec![0.0; stations.len()];
+    let mut lines_by_index: HashMap<usize, usize> = HashMap::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (index, line) in reader.split(b'\n').enumerate() {
+        let number: usize = index + 1;
+        let line: Vec<u8> = line?;
+        let line: &[u8] = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+
+        let Some((name, weight)) = std::str::from_utf8(line)
+            .ok()
+            .and_then(|line: &str| line.rsplit_once(';'))
+        else {
+            errors.push(format!(
+                "line {}: expected `name;weight`, found {:?}",
+                number,
+                String::from_utf8_lossy(line)
+            ));
+            continue;
+        };
+        match (
+            indexes.get(name),
+            weight
+                .parse::<f64>()
+                .ok()
+                .filter(|w: &f64| w.is_finite() && *w >= 0.0),
+        ) {
+            (None, _) => errors.push(format!("line {}: unknown station {:?}", number, name)),
+            (_, None) => errors.push(format!("line {}: invalid weight {:?}", number, weight)),
+            (Some(station), Some(weight)) => match lines_by_index.get(station) {
+                Some(first) => errors.push(format!(
+                    "line {}: duplicate name {:?}, first on line {}",
+                    number, name, first
+                )),
+                None => {
+                    lines_by_index.insert(*station, number);
+                    weights[*station] = weight;
+                }
+            },
+        }
+    }
+
+    if errors.is_empty() && !weights.iter().any(|weight: &f64| *weight > 0.0) {
+        errors.push(String::from("no positive weight"));
+    }
+    if errors.is_empty() {
+        Ok(weights)
+    } else {
+        Err(ObrcError::InvalidStationsFile(errors))
+    }
+}
+
+type CatalogLine = (String, f64, Option<TemperatureDistribution>);
+
+/// Parse the name, mean and distribution of a `name;mean[;distribution]` line.
+fn parse_catalog_line(line: &[u8]) -> Result<CatalogLine, String> {
+    let line: &str = std::str::from_utf8(line).map_err(|_| String::from("not UTF-8"))?;
+    let parse =
+        |value: &str| -> Option<f64> { value.parse::<f64>().ok().filter(|v| v.is_finite()) };
+
+    // Numbers are taken from the end of the line, so that a `;` in the name can be told apart.
+    let (rest, last) = line
+        .rsplit_once(';')
+        .ok_or_else(|| format!("expected `name;mean[;distribution]`, found {:?}", line))?;
+    let (name, mean, distribution): (&str, &str, Option<&str>) = match rest.rsplit_once(';') {
+        Some((name, mean)) if parse(mean).is_some() => (name, mean, Some(last)),
+        _ => (rest, last, None),
+    };
+
+    if name.is_empty() {
+        return Err(String::from("empty name"));
+    }
+    if name.contains(';') {
+        return Err(format!("name {:?} contains `;`", name));
+    }
+    if name.contains(['\n', '\r']) {
+        return Err(format!("name {:?} contains a newline character", name));
+    }
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "name {:?} is {} bytes long, over {} bytes",
+            name,
+            name.len(),
+            MAX_NAME_LENGTH
+        ));
+    }
+    let mean: f64 = parse(mean).ok_or_else(|| format!("invalid mean {:?}", mean))?;
+    if !(-MAX_TEMPERATURE..=MAX_TEMPERATURE).contains(&mean) {
+        return Err(format!(
+            "mean {} is outside [{}, {}]",
+            mean, -MAX_TEMPERATURE, MAX_TEMPERATURE
+        ));
+    }
+    let distribution: Option<TemperatureDistribution> = distribution
+        .map(|distribution: &str| distribution.parse::<TemperatureDistribution>())
+        .transpose()?;
+
+    Ok((String::from(name), mean, distribution))
+}
+
+/// Number of distinct names of `name_length` bytes that [`synthesize_weather_stations`] can draw,
+/// saturating at `u128::MAX`.
+pub fn name_capacity(name_length: RangeInclusive<usize>) -> u128 {
+    // Names of every length in bytes, by the byte length of their last character.
+    let mut names: Vec<u128> = vec![0; *name_length.end() + 1];
+    names[0] = 1;
+    for length in 1..names.len() {
+        names[length] = NAME_CHARS
+            .iter()
+            .filter(|c: &&char| c.len_utf8() <= length)
+            .fold(0, |total: u128, c: &char| {
+                total.saturating_add(names[length - c.len_utf8()])
+            });
+    }
+    names[name_length]
+        .iter()
+        .fold(0, |total: u128, count: &u128| total.saturating_add(*count))
+}
+
+/// Synthesize `count` weather stations with unique names, of `name_length` bytes,
+/// mean temperatures between -20 and 30 degrees and the given distribution.
+///
+/// Names mix multi-byte characters in with ASCII, and a quarter of them share a long prefix
+/// with a previous name, to stress hashing and name comparisons.
+///
+/// # Panics
+/// If `name_length` is not within 1 and [`MAX_NAME_LENGTH`] bytes,
+/// or does not allow `count` unique names: its [`name_capacity`] should leave room for random draws,
+/// e.g. twice `count`.
+pub fn synthesize_weather_stations<R: Rng + ?Sized>(
+    count: usize,
+    name_length: RangeInclusive<usize>,
+    distribution: TemperatureDistribution,
+    rng: &mut R,
+) -> Vec<WeatherStation> {
+    assert!(
+        *name_length.start() >= 1
+            && name_length.start() <= name_length.end()
+            && *name_length.end() <= MAX_NAME_LENGTH,
+        "Name length {:?} is not within 1 and {} bytes",
+        name_length,
+        MAX_NAME_LENGTH
+    );
+
+    let mut names: Vec<String> = Vec::with_capacity(count);
+    let mut taken: HashSet<String> = HashSet::with_capacity(count);
+    while names.len() < count {
+        let name: String = (0..NAME_ATTEMPTS)
+            .map(|_| synthesize_name(&names, rng.gen_range(name_length.clone()), rng))
+            .find(|name: &String| !taken.contains(name))
+            .unwrap_or_else(|| {
+                panic!(
+                    "Cannot synthesize {} unique names of {:?} bytes",
+                    count, name_length
+                )
+            });
+        taken.insert(name.clone());
+        names.push(name);
+    }
+
+    names
+        .into_iter()
+        .map(|name: String| {
+            let mean: f64 = (rng.gen_range(-200..=300) as f64) / 10.0;
+            WeatherStation::with_distribution(name, mean, distribution)
+        })
+        .collect()
+}
+
+/// Synthesize a name of exactly `length` bytes,
+/// starting with a prefix of one of the previous names a quarter of the time.
+fn synthesize_name<R: Rng + ?Sized>(previous: &[String], length: usize, rng: &mut R) -> String {
+    let mut name: String = String::with_capacity(length);
+
+    if length > 1 && rng.gen_ratio(1, 4) {
+        if let Some(other) = previous.choose(rng) {
+            let prefix: usize = (0..length.min(other.len()))
+                .rev()
+                .find(|end: &usize| other.is_char_boundary(*end))
+                .unwrap_or(0);
+            name.push_str(&other[..prefix]);
+        }
+    }
+
+    while name.len() < length {
+        let remaining: usize = length - name.len();
+        let c: char = *NAME_CHARS.choose(rng).unwrap();
+        if c.len_utf8() <= remaining {
+            name.push(c);
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::get_weather_stations;
+    use super::name_capacity;
+    use super::read_station_weights;
+    use super::read_weather_stations;
+    use super::synthesize_weather_stations;
+    use super::WeatherStation;
+    use crate::generator::distribution::TemperatureDistribution;
+    use crate::solutions::error::ObrcError;
+
+    #[test]
+    fn test_read_weather_stations() {
+        let catalog: &[u8] =
+            "# name;mean[;distribution]\r\nHamburg;9.7\r\n\nİzmir;17.9;3.5\nSt. John's;-5;uniform:8\n"
+                .as_bytes();
+
+        let stations: Vec<WeatherStation> =
+            read_weather_stations(catalog, TemperatureDistribution::Normal { std_dev: 2.0 })
+                .unwrap();
+
+        assert_eq!(
+            vec!["Hamburg", "İzmir", "St. John's"],
+            stations
+                .iter()
+                .map(|station: &WeatherStation| station.name.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            TemperatureDistribution::Normal { std_dev: 2.0 },
+            stations[0].distribution()
+        );
+        assert_eq!(
+            TemperatureDistribution::Normal { std_dev: 3.5 },
+            stations[1].distribution()
+        );
+        assert_eq!(
+            TemperatureDistribution::Uniform { half_width: 8.0 },
+            stations[2].distribution()
+        );
+        assert_eq!(-5.0, stations[2].mean());
+    }
+
+    #[test]
+    fn test_read_weather_stations_invalid() {
+        let long_name: String = "a".repeat(101);
+        let catalog: String = format!(
+            "Hamburg;9.7\nHamburg;10.0\nHam;burg;9.7\nHam;burg;9.7;2.0\nHamburg\n;9.7\n\
+             {};9.7\nBulawayo;100.0\nBulawayo;warm\nBulawayo;18.9;-1\nBulawayo;18.9;1;2\nBulawayo;18.9;normal\n",
+            long_name
+        );
+
+        let Err(ObrcError::InvalidStationsFile(errors)) =
+            read_weather_stations(catalog.as_bytes(), TemperatureDistribution::default())
+        else {
+            panic!("expected an invalid stations file");
+        };
+
+        assert_eq!(
+            vec![
+                String::from("line 2: duplicate name \"Hamburg\", first on line 1"),
+                String::from("line 3: name \"Ham;burg\" contains `;`"),
+                String::from("line 4: name \"Ham;burg\" contains `;`"),
+                String::from("line 5: expected `name;mean[;distribution]`, found \"Hamburg\""),
+                String::from("line 6: empty name"),
+                format!(
+                    "line 7: name {:?} is 101 bytes long, over 100 bytes",
+                    long_name
+                ),
+                String::from("line 8: mean 100 is outside [-99.9, 99.9]"),
+                String::from("line 9: invalid mean \"warm\""),
+                String::from("line 10: \"-1\" is not a valid temperature distribution"),
+                String::from("line 11: name \"Bulawayo;18.9\" contains `;`"),
+                String::from("line 12: \"normal\" is not a valid temperature distribution"),
+            ],
+            errors
+        );
+        assert!(matches!(
+            read_weather_stations(b"# empty\n".as_slice(), TemperatureDistribution::default()),
+            Err(ObrcError::InvalidStationsFile(errors)) if errors == vec!["no weather stations"]
+        ));
+    }
+
+    #[test]
+    fn test_read_station_weights() {
+        let stations: Vec<WeatherStation> = vec![
+            WeatherStation::new("Hamburg", 9.7),
+            WeatherStation::new("İzmir", 17.9),
+            WeatherStation::new("Bulawayo", 18.9),
+        ];
+
+        assert_eq!(
+            vec![2.5, 0.0, 1.0],
+            read_station_weights(
+                "# name;weight\nBulawayo;1\r\n\nHamburg;2.5\n".as_bytes(),
+                &stations
+            )
+            .unwrap()
+        );
+
+        let Err(ObrcError::InvalidStationsFile(errors)) = read_station_weights(
+            "Hamburg;1\nHamburg;2\nParis;1\nİzmir;-1\nİzmir;heavy\nBulawayo\n".as_bytes(),
+            &stations,
+        ) else {
+            panic!("expected an invalid weights file");
+        };
+        assert_eq!(
+            vec![
+                "line 2: duplicate name \"Hamburg\", first on line 1",
+                "line 3: unknown station \"Paris\"",
+                "line 4: invalid weight \"-1\"",
+                "line 5: invalid weight \"heavy\"",
+                "line 6: expected `name;weight`, found \"Bulawayo\"",
+            ],
+            errors
+        );
+        assert!(matches!(
+            read_station_weights(b"Hamburg;0\n".as_slice(), &stations),
+            Err(ObrcError::InvalidStationsFile(errors)) if errors == vec!["no positive weight"]
+        ));
+    }
+
+    #[test]
+    fn test_name_capacity() {
+        // 27 single-byte characters, 3 of two bytes, 1 of three and 1 of four.
+        assert_eq!(27, name_capacity(1..=1));
+        assert_eq!(27 * 27 + 3, name_capacity(2..=2));
+        assert_eq!(27 + 27 * 27 + 3, name_capacity(1..=2));
+        assert_eq!(27 * 27 * 27 + 2 * 27 * 3 + 1, name_capacity(3..=3));
+        assert_eq!(u128::MAX, name_capacity(1..=100));
+    }
+
+    #[test]
+    fn test_synthesize_weather_stations() {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(42);
+
+        let stations: Vec<WeatherStation> = synthesize_weather_stations(
+            10_000,
+            1..=100,
+            TemperatureDistribution::default(),
+            &mut rng,
+        );
+
+        let names: HashSet<&str> = stations
+            .iter()
+            .map(|station: &WeatherStation| station.name.as_str())
+            .collect();
+        assert_eq!(10_000, names.len());
+        assert!(names
+            .iter()
+            .all(|name: &&str| (1..=100).contains(&name.len())
+                && !name.contains(';')
+                && !name.contains('\n')));
+        assert!(names
+            .iter()
+            .any(|name: &&str| name.len() > name.chars().count()));
+    }
+
+    #[test]
+    fn test_synthesize_weather_stations_exact_length() {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(42);
+
+        let stations: Vec<WeatherStation> =
+            synthesize_weather_stations(500, 7..=7, TemperatureDistribution::default(), &mut rng);
+
+        assert!(stations
+            .iter()
+            .all(|station: &WeatherStation| station.name.len() == 7));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot synthesize 100 unique names of 1..=1 bytes")]
+    fn test_synthesize_weather_stations_too_many() {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(42);
+
+        synthesize_weather_stations(100, 1..=1, TemperatureDistribution::default(), &mut rng);
+    }
+
+    #[test]
+    fn test_get_measurement_is_clamped() {
+        let mut rng: ChaCha8Rng = ChaCha8Rng::seed_from_u64(42);
+        let station: WeatherStation = WeatherStation::with_distribution(
+            "Hell",
+            95.0,
+            TemperatureDistribution::StudentT {
+                degrees_of_freedom: 1.0,
+                scale: 10.0,
+            },
+        );
+
+        let measurements: Vec<f64> = (0..10_000)
+            .map(|_| station.get_measurement(&mut rng))
+            .collect();
+
+        assert!(measurements
+            .iter()
+            .all(|t: &f64| (-99.9..=99.9).contains(t)));
+        assert!(measurements.contains(&99.9));
+        assert!(measurements.contains(&-99.9));
+        assert!(get_weather_stations()
+            .iter()
+            .all(|station: &WeatherStation| station.distribution()
+                == TemperatureDistribution::default()));
+    }
+}
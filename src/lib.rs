@@ -1,11 +1,35 @@
 pub mod utils {
+    pub mod compression;
     pub mod parsing;
     pub mod rounding;
+    pub mod scanner;
+    pub mod station_table;
+}
+
+pub mod generator {
+    pub mod corruption;
+    pub mod distribution;
+    pub mod measurements;
+    pub mod weather_stations;
+}
+
+pub mod output {
+    #[cfg(feature = "arrow")]
+    pub mod arrow;
+    pub mod formatter;
 }
 
 pub mod solutions {
+    pub mod aggregation;
+    pub mod chunks;
+    pub mod error;
+    pub mod partial;
+    pub mod rejected;
     pub mod solution_basic;
+    pub mod solution_mmap;
     pub mod solution_naive;
+    pub mod solution_open_addressing;
     pub mod solution_rayon_fxhash;
+    pub mod solution_streaming;
     pub mod solver;
 }
@@ -1,24 +1,113 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::stdout;
+use std::io::BufReader;
 use std::io::BufWriter;
+use std::num::NonZeroUsize;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process::exit;
+use std::thread::available_parallelism;
+use std::time::Duration;
 use std::time::Instant;
 
+use clap::error::ErrorKind;
+use clap::CommandFactory;
 use clap::Parser;
+use clap::Subcommand;
 use clap::ValueEnum;
+use glob::glob;
+use glob::GlobError;
+use glob::PatternError;
+#[cfg(feature = "arrow")]
+use obrc::output::arrow::write_ipc;
+#[cfg(feature = "arrow")]
+use obrc::output::arrow::write_parquet;
+use obrc::output::formatter::format_with_columns;
+use obrc::output::formatter::Columns;
+use obrc::output::formatter::Format;
+use obrc::solutions::aggregation::Aggregation;
+use obrc::solutions::aggregation::Statistics;
+use obrc::solutions::error::ObrcError;
+use obrc::solutions::partial::read_partial;
+use obrc::solutions::partial::write_partial;
 use obrc::solutions::solution_basic::SolutioBasic;
+use obrc::solutions::solution_mmap::SolutionMmap;
 use obrc::solutions::solution_naive::SolutioNaive;
+use obrc::solutions::solution_open_addressing::SolutionOpenAddressing;
 use obrc::solutions::solution_rayon_fxhash::SolutionRayonFxHash;
+use obrc::solutions::solution_streaming::SolutionStreaming;
+use obrc::solutions::solver::OnError;
 use obrc::solutions::solver::Solver;
+use obrc::solutions::solver::SolverOptions;
+use obrc::utils::compression::Compression;
+use rayon::prelude::*;
 
 #[derive(Parser)]
 #[command()]
 struct Cli {
-    #[arg(short, long, default_value_t = String::from("measurements_1000000000.txt"))]
-    input_path: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Paths of the measurements files, or glob patterns like `shards/*.txt`, aggregated into one result;
+    /// or `-` alone to read them from stdin (streaming solution only)
+    #[arg(short, long, num_args = 1.., default_value = "measurements_1000000000.txt")]
+    input_path: Vec<String>,
+
+    /// Report the rows of every input file on stderr
+    #[arg(short, long)]
+    verbose: bool,
 
     #[arg(short, long, value_enum, default_value_t = Solution::Naive)]
     solution: Solution,
+
+    /// Path of the results, or `-` to print them to stdout; by default `results_<solution>`
+    /// replaces `measurements` in the input file name, or prefixes it, or is `results_<solution>.txt`
+    /// for several input files, and stdout for `merge`
+    #[arg(short, long, global = true)]
+    output: Option<String>,
+
+    #[arg(short, long, value_enum, global = true, default_value_t = Format::Official)]
+    format: Format,
+
+    /// Also render the standard deviation, variance and sum of every weather station
+    #[arg(long, global = true)]
+    extended_statistics: bool,
+
+    /// Also render these exact percentiles of every weather station, e.g. `50,90,99`
+    #[arg(long, global = true, value_delimiter = ',', value_parser = parse_percentile)]
+    percentiles: Vec<f64>,
+
+    /// Also write the aggregation to this path as a partial result, to be combined with `merge`,
+    /// or to stdout for `-`
+    #[arg(long)]
+    partial_output: Option<String>,
+
+    /// Also write the station, min, max, mean and count of every weather station as an Arrow IPC file
+    #[cfg(feature = "arrow")]
+    #[arg(long)]
+    arrow_output: Option<String>,
+
+    /// Also write the station, min, max, mean and count of every weather station as a Parquet file
+    #[cfg(feature = "arrow")]
+    #[arg(long)]
+    parquet_output: Option<String>,
+
+    /// What to do with malformed lines: `fail` on the first one, `skip` them,
+    /// or `quarantine=<path>` to skip them and write them to that file (`<path>.<n>` for the n-th of several inputs)
+    #[arg(long, default_value_t = OnError::Fail)]
+    on_error: OnError,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Combine partial results written with `--partial-output` into the final result
+    Merge {
+        /// Paths of the partial results
+        #[arg(required = true)]
+        partials: Vec<String>,
+    },
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -26,40 +115,752 @@ enum Solution {
     Basic,
     Naive,
     RayonFxHash,
+    Mmap,
+    OpenAddressing,
+    Streaming,
+    /// Run every solution on the input, check that they agree and compare their timings
+    All,
 }
 
+/// Every solution, [`Solution::Naive`] first as the reference of comparisons.
+const SOLUTIONS: [Solution; 6] = [
+    Solution::Naive,
+    Solution::Basic,
+    Solution::RayonFxHash,
+    Solution::Mmap,
+    Solution::OpenAddressing,
+    Solution::Streaming,
+];
+
 fn main() {
     let cli: Cli = Cli::parse();
 
-    assert!(
-        PathBuf::from(&cli.input_path).exists(),
-        "Path {:?} does not exist!",
-        &cli.input_path
-    );
+    match &cli.command {
+        Some(Command::Merge { partials }) => merge(partials, &cli),
+        None if cli.solution == Solution::All => compare(&cli),
+        None => solve(&cli),
+    }
+}
+
+fn solve(cli: &Cli) {
+    let inputs: Vec<String> = inputs(cli);
+    let output: String = cli
+        .output
+        .clone()
+        .unwrap_or_else(|| default_output(&inputs, cli.solution));
+    let options: SolverOptions = options(cli);
+    let results: Vec<&String> = [Some(&output), cli.partial_output.as_ref()]
+        .into_iter()
+        .chain(exports(cli))
+        .flatten()
+        .collect();
+    for (index, path) in results.iter().enumerate() {
+        if results[index + 1..]
+            .iter()
+            .any(|other: &&String| is_same_output(path, other))
+        {
+            match path.as_str() {
+                "-" => eprintln!("Refusing to write two results to stdout"),
+                path => eprintln!("Refusing to write two results to {}", path),
+            }
+            exit(1);
+        }
+    }
+    let outputs: Vec<&String> = results
+        .into_iter()
+        .filter(|path: &&String| *path != "-")
+        .collect();
+    let quarantines: Vec<String> = (0..inputs.len())
+        .filter_map(
+            |index: usize| match file_options(&options, inputs.len(), index).on_error {
+                OnError::Quarantine(path) => Some(path),
+                _ => None,
+            },
+        )
+        .collect();
+    for path in outputs.iter().copied().chain(&quarantines) {
+        if let Some(input) = inputs
+            .iter()
+            .find(|input: &&String| *input != "-" && is_same_file(path, input))
+        {
+            eprintln!("Refusing to overwrite the input {} with results", input);
+            exit(1);
+        }
+    }
+    for quarantine in &quarantines {
+        if let Some(path) = outputs
+            .iter()
+            .find(|path: &&&String| is_same_file(path, quarantine))
+        {
+            eprintln!(
+                "Refusing to overwrite the results {} with malformed lines",
+                path
+            );
+            exit(1);
+        }
+    }
 
     let now: Instant = Instant::now();
-    let output: String = match cli.solution {
-        Solution::Basic => SolutioBasic::solve_obrc(&cli.input_path),
-        Solution::Naive => SolutioNaive::solve_obrc(&cli.input_path),
-        Solution::RayonFxHash => SolutionRayonFxHash::solve_obrc(&cli.input_path),
+    let result: Result<Aggregation, String> =
+        run_files(cli.solution, &inputs, &options, cli.verbose);
+    let aggregation: Aggregation = result.unwrap_or_else(|error: String| {
+        eprintln!(
+            "{:?} solution for {} failed: {}",
+            cli.solution,
+            describe(&inputs),
+            error
+        );
+        exit(1);
+    });
+
+    eprintln!(
+        "{:?} solution for {} generated in {:?} ({} rejected lines)",
+        cli.solution,
+        describe(&inputs),
+        now.elapsed(),
+        aggregation.rejected
+    );
+
+    write_results(&aggregation, &output, cli);
+
+    match cli.partial_output.as_deref() {
+        Some("-") => write_partial(&aggregation, BufWriter::new(stdout().lock())).unwrap(),
+        Some(partial_output) => {
+            write_partial(&aggregation, BufWriter::new(create_output(partial_output))).unwrap()
+        }
+        None => {}
+    }
+    #[cfg(feature = "arrow")]
+    export(&aggregation, cli);
+}
+
+/// The input files, glob patterns expanded, checked to exist and to be readable by the solution.
+fn inputs(cli: &Cli) -> Vec<String> {
+    let inputs: Vec<String> = expand_inputs(&cli.input_path).unwrap_or_else(|error: String| {
+        eprintln!("{}", error);
+        exit(1);
+    });
+    let from_stdin: bool = inputs.iter().any(|input: &String| input == "-");
+    if from_stdin && inputs.len() > 1 {
+        usage_error(
+            ErrorKind::ArgumentConflict,
+            "--input-path - reads from stdin, and excludes any other input",
+        );
+    }
+    if from_stdin && cli.solution != Solution::Streaming {
+        usage_error(
+            ErrorKind::ArgumentConflict,
+            "--input-path - reads from stdin, which only --solution streaming supports",
+        );
+    }
+    if let Some(input) = inputs
+        .iter()
+        .find(|input: &&String| *input != "-" && !PathBuf::from(input).exists())
+    {
+        usage_error(
+            ErrorKind::ValueValidation,
+            &format!("--input-path {} does not exist", input),
+        );
+    }
+    inputs
+}
+
+/// Exit with a clap error about the usage of the command line arguments.
+fn usage_error(kind: ErrorKind, message: &str) -> ! {
+    Cli::command().error(kind, message).exit()
+}
+
+/// Expand the glob patterns among the input paths, in order and without duplicates.
+/// Other paths, `-` included, are kept as they are.
+fn expand_inputs(patterns: &[String]) -> Result<Vec<String>, String> {
+    let mut inputs: Vec<String> = Vec::new();
+    for pattern in patterns {
+        if !pattern.contains(['*', '?', '[']) {
+            inputs.push(pattern.clone());
+            continue;
+        }
+        let paths: Vec<PathBuf> = glob(pattern)
+            .map_err(|error: PatternError| format!("Invalid pattern {:?}: {}", pattern, error))?
+            .collect::<Result<Vec<PathBuf>, GlobError>>()
+            .map_err(|error: GlobError| format!("Cannot expand {:?}: {}", pattern, error))?;
+        if paths.is_empty() {
+            return Err(format!("Pattern {:?} matches no file", pattern));
+        }
+        inputs.extend(
+            paths
+                .into_iter()
+                .map(|path: PathBuf| path.to_string_lossy().into_owned()),
+        );
+    }
+    let mut seen: HashSet<String> = HashSet::new();
+    inputs.retain(|input: &String| seen.insert(input.clone()));
+    Ok(inputs)
+}
+
+/// The input file, or the number of input files.
+fn describe(inputs: &[String]) -> String {
+    match inputs {
+        [input] => input.clone(),
+        inputs => format!("{} files", inputs.len()),
+    }
+}
+
+fn options(cli: &Cli) -> SolverOptions {
+    SolverOptions {
+        histograms: !cli.percentiles.is_empty(),
+        sum_squares: cli.extended_statistics,
+        on_error: cli.on_error.clone(),
+        threads: None,
+    }
+}
+
+/// Paths of the optional Arrow IPC and Parquet exports.
+#[cfg(feature = "arrow")]
+fn exports(cli: &Cli) -> [Option<&String>; 2] {
+    [cli.arrow_output.as_ref(), cli.parquet_output.as_ref()]
+}
+
+#[cfg(not(feature = "arrow"))]
+fn exports(_cli: &Cli) -> [Option<&String>; 0] {
+    []
+}
+
+/// Write the optional Arrow IPC and Parquet exports.
+#[cfg(feature = "arrow")]
+fn export(aggregation: &Aggregation, cli: &Cli) {
+    if let Some(arrow_output) = &cli.arrow_output {
+        write_ipc(aggregation, BufWriter::new(create_output(arrow_output))).unwrap_or_else(
+            |error: ObrcError| {
+                eprintln!("Cannot write {}: {}", arrow_output, error);
+                exit(1);
+            },
+        );
+    }
+    if let Some(parquet_output) = &cli.parquet_output {
+        write_parquet(aggregation, create_output(parquet_output)).unwrap_or_else(
+            |error: ObrcError| {
+                eprintln!("Cannot write {}: {}", parquet_output, error);
+                exit(1);
+            },
+        );
+    }
+}
+
+/// Aggregate the input files into one result, with the [`file_options`] of every file.
+/// The files are solved in parallel; the solutions that start their own threads share the cores
+/// among the files solved at once, and the others share rayon's pool.
+/// Returns the error of the first failing file, in the order of the inputs.
+fn run_files(
+    solution: Solution,
+    inputs: &[String],
+    options: &SolverOptions,
+    verbose: bool,
+) -> Result<Aggregation, String> {
+    let cores: usize = available_parallelism().map_or(1, NonZeroUsize::get);
+    let concurrent: usize = inputs.len().min(rayon::current_num_threads()).max(1);
+    let options: SolverOptions = SolverOptions {
+        threads: options
+            .threads
+            .or_else(|| NonZeroUsize::new(cores.div_ceil(concurrent))),
+        ..options.clone()
     };
 
+    inputs
+        .par_iter()
+        .enumerate()
+        .map(|(index, input): (usize, &String)| {
+            run_file(solution, inputs, index, input, &options, verbose)
+                .map_err(|error: String| (index, error))
+        })
+        .reduce(
+            || Ok(Aggregation::default()),
+            |left: Result<Aggregation, (usize, String)>,
+             right: Result<Aggregation, (usize, String)>| match (left, right) {
+                (Ok(mut aggregation), Ok(other)) => {
+                    aggregation.merge(other);
+                    Ok(aggregation)
+                }
+                (Err(left), Err(right)) => Err(left.min(right)),
+                (Err(error), Ok(_)) | (Ok(_), Err(error)) => Err(error),
+            },
+        )
+        .map_err(|(_, error): (usize, String)| error)
+}
+
+/// Aggregate the `index`-th of the input files.
+fn run_file(
+    solution: Solution,
+    inputs: &[String],
+    index: usize,
+    input: &str,
+    options: &SolverOptions,
+    verbose: bool,
+) -> Result<Aggregation, String> {
+    let options: SolverOptions = file_options(options, inputs.len(), index);
+    let now: Instant = Instant::now();
+    let aggregation: Aggregation =
+        run(solution, input, &options).map_err(|error: ObrcError| match inputs {
+            [_] => error.to_string(),
+            _ => format!("{}: {}", input, error),
+        })?;
+    if verbose {
+        eprintln!(
+            "{}: {} rows ({} rejected) in {:?}",
+            input,
+            rows(&aggregation),
+            aggregation.rejected,
+            now.elapsed()
+        );
+    }
+    Ok(aggregation)
+}
+
+/// Options of the `index`-th of `files` input files:
+/// under [`OnError::Quarantine`], the malformed lines of the n-th of several files go to `<path>.<n>`.
+fn file_options(options: &SolverOptions, files: usize, index: usize) -> SolverOptions {
+    match &options.on_error {
+        OnError::Quarantine(path) if files > 1 => SolverOptions {
+            on_error: OnError::Quarantine(format!("{}.{}", path, index + 1)),
+            ..options.clone()
+        },
+        _ => options.clone(),
+    }
+}
+
+/// Number of rows of the input, malformed lines included.
+fn rows(aggregation: &Aggregation) -> u64 {
+    aggregation.rejected
+        + aggregation
+            .stations
+            .values()
+            .map(Statistics::count)
+            .sum::<u64>()
+}
+
+fn run(
+    solution: Solution,
+    input_path: &str,
+    options: &SolverOptions,
+) -> Result<Aggregation, ObrcError> {
+    if solution != Solution::Streaming {
+        let compression: Compression = Compression::of_file(input_path)?;
+        if compression != Compression::None {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!(
+                    "{} input is only supported by the {:?} solution",
+                    compression,
+                    Solution::Streaming
+                ),
+            )
+            .into());
+        }
+    }
+    match solution {
+        Solution::Basic => SolutioBasic::solve_obrc_with_options(input_path, options),
+        Solution::Naive => SolutioNaive::solve_obrc_with_options(input_path, options),
+        Solution::RayonFxHash => SolutionRayonFxHash::solve_obrc_with_options(input_path, options),
+        Solution::Mmap => SolutionMmap::solve_obrc_with_options(input_path, options),
+        Solution::OpenAddressing => {
+            SolutionOpenAddressing::solve_obrc_with_options(input_path, options)
+        }
+        Solution::Streaming => SolutionStreaming::solve_obrc_with_options(input_path, options),
+        Solution::All => unreachable!("{:?} is not a single solution", solution),
+    }
+}
+
+/// Run every solution on the input and print a table of their timings,
+/// exiting with an error if any of them fails or disagrees with [`Solution::Naive`].
+/// Malformed lines can be skipped, but not quarantined, as every solution would write the same file.
+fn compare(cli: &Cli) {
+    if let OnError::Quarantine(_) = cli.on_error {
+        usage_error(
+            ErrorKind::ArgumentConflict,
+            "--on-error quarantine=<path> would make every solution overwrite the same file, use --on-error skip with --solution all",
+        );
+    }
+    let inputs: Vec<String> = inputs(cli);
+    let bytes: u64 = inputs
+        .iter()
+        .map(|input: &String| {
+            std::fs::metadata(input).map(|metadata: std::fs::Metadata| metadata.len())
+        })
+        .sum::<std::io::Result<u64>>()
+        .unwrap_or_else(|error: std::io::Error| {
+            eprintln!(
+                "Cannot compare solutions on {}: {}",
+                describe(&inputs),
+                error
+            );
+            exit(1);
+        });
+    let options: SolverOptions = options(cli);
+
+    let runs: Vec<(Solution, Duration, Result<Aggregation, String>)> = SOLUTIONS
+        .iter()
+        .map(|solution: &Solution| {
+            let now: Instant = Instant::now();
+            let result: Result<Aggregation, String> =
+                run_files(*solution, &inputs, &options, cli.verbose);
+            (*solution, now.elapsed(), result)
+        })
+        .collect();
+    let (_, naive_duration, naive_result) = &runs[0];
+
     println!(
-        "{:?} solution for {} generated in {:?}",
-        cli.solution,
-        &cli.input_path,
-        now.elapsed()
+        "{:<16} {:>12} {:>14} {:>8} {:>8}",
+        "solution", "time", "rows/s", "GB/s", "speedup"
     );
+    for (solution, duration, result) in &runs {
+        let seconds: f64 = duration.as_secs_f64();
+        match result {
+            Ok(aggregation) => {
+                println!(
+                    "{:<16} {:>12} {:>14.0} {:>8.3} {:>7.2}x",
+                    format!("{:?}", solution),
+                    format!("{:.3?}", duration),
+                    rows(aggregation) as f64 / seconds,
+                    bytes as f64 / seconds / 1e9,
+                    naive_duration.as_secs_f64() / seconds
+                );
+            }
+            Err(error) => println!("{:<16} failed: {}", format!("{:?}", solution), error),
+        }
+    }
+
+    let mut disagreements: usize = 0;
+    for (solution, _, result) in &runs[1..] {
+        let disagreement: Option<String> = match (naive_result, result) {
+            (Ok(expected), Ok(actual)) => {
+                let stations: Vec<&str> = expected.differences(actual);
+                if !stations.is_empty() {
+                    Some(format!("on stations {}", stations.join(", ")))
+                } else if expected.rejected != actual.rejected {
+                    Some(format!(
+                        "on rejected lines, {} instead of {}",
+                        actual.rejected, expected.rejected
+                    ))
+                } else {
+                    None
+                }
+            }
+            (Ok(_), Err(_)) | (Err(_), Ok(_)) => Some(String::from("on failing")),
+            (Err(expected), Err(actual)) if expected != actual => {
+                Some(format!("on the error, {}", actual))
+            }
+            (Err(_), Err(_)) => None,
+        };
+        if let Some(disagreement) = disagreement {
+            eprintln!(
+                "{:?} disagrees with {:?} {}",
+                solution,
+                Solution::Naive,
+                disagreement
+            );
+            disagreements += 1;
+        }
+    }
+    if disagreements > 0 || naive_result.is_err() {
+        exit(1);
+    }
+}
+
+/// Default path of the results: [`result_path`] of a single input,
+/// or `results_<solution>.txt` in the current directory for several.
+fn default_output(inputs: &[String], solution: Solution) -> String {
+    match inputs {
+        [input] => result_path(input, solution),
+        _ => format!("results_{:?}.txt", solution).to_lowercase(),
+    }
+}
+
+/// Path of the results of a single input: `results_<solution>` in place of `measurements` in the file name
+/// of the input, or as a prefix of it, in the same directory.
+fn result_path(input_path: &str, solution: Solution) -> String {
+    let prefix: String = format!("results_{:?}", solution).to_lowercase();
+    if input_path == "-" {
+        return format!("{}_stdin.txt", prefix);
+    }
+    let path: &Path = Path::new(input_path);
+    let file_name: String = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let file_name: String = if file_name.contains("measurements") {
+        file_name.replacen("measurements", &prefix, 1)
+    } else {
+        format!("{}_{}", prefix, file_name)
+    };
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Whether both paths lead to the same file, existing or to be created in an existing directory.
+fn is_same_file(path: &str, other: &str) -> bool {
+    match (canonical_path(path), canonical_path(other)) {
+        (Some(path), Some(other)) => path == other,
+        _ => false,
+    }
+}
+
+/// Whether both paths of results lead to the same file, or are both `-` for stdout.
+fn is_same_output(path: &str, other: &str) -> bool {
+    path == other || (path != "-" && other != "-" && is_same_file(path, other))
+}
+
+/// Absolute path of a file without symbolic links, or of a file to be created in an existing directory.
+fn canonical_path(path: &str) -> Option<PathBuf> {
+    let path: &Path = Path::new(path);
+    path.canonicalize().ok().or_else(|| {
+        let parent: &Path = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        Some(parent.canonicalize().ok()?.join(path.file_name()?))
+    })
+}
+
+fn create_output(path: &str) -> File {
+    File::create(path).unwrap_or_else(|error: std::io::Error| {
+        eprintln!("Cannot create {}: {}", path, error);
+        exit(1);
+    })
+}
+
+fn merge(partials: &[String], cli: &Cli) {
+    let output: &str = cli.output.as_deref().unwrap_or("-");
+    if let Some(partial) = partials
+        .iter()
+        .find(|partial: &&String| output != "-" && is_same_file(output, partial))
+    {
+        eprintln!(
+            "Refusing to overwrite the partial result {} with results",
+            partial
+        );
+        exit(1);
+    }
+    let mut aggregation: Aggregation = Aggregation::default();
+
+    for partial in partials {
+        let result: Result<Aggregation, ObrcError> = File::open(partial)
+            .map_err(ObrcError::from)
+            .and_then(|file: File| read_partial(BufReader::new(file)));
+        aggregation.merge(result.unwrap_or_else(|error: ObrcError| {
+            eprintln!("Cannot read partial result {}: {}", partial, error);
+            exit(1);
+        }));
+    }
+
+    if !cli.percentiles.is_empty()
+        && aggregation
+            .stations
+            .values()
+            .any(|stats: &Statistics| stats.histogram().is_none())
+    {
+        eprintln!("Percentiles require partial results written with --percentiles");
+        exit(1);
+    }
+    if cli.extended_statistics
+        && aggregation
+            .stations
+            .values()
+            .any(|stats: &Statistics| stats.variance().is_none())
+    {
+        eprintln!("Extended statistics require partial results written with --extended-statistics");
+        exit(1);
+    }
+
+    write_results(&aggregation, output, cli);
+}
+
+/// Write the results in the format and with the columns of the command line, to the file at `output`
+/// or to stdout for `-`.
+fn write_results(aggregation: &Aggregation, output: &str, cli: &Cli) {
+    let results: String = format_with_columns(aggregation, cli.format, &columns(cli));
+    if output == "-" {
+        let mut writer: BufWriter<_> = BufWriter::new(stdout().lock());
+        write!(writer, "{}", results).unwrap();
+        writer.flush().unwrap();
+    } else {
+        let mut writer: BufWriter<File> = BufWriter::new(create_output(output));
+        write!(writer, "{}", results).unwrap();
+        writer.flush().unwrap();
+    }
+}
+
+fn columns(cli: &Cli) -> Columns {
+    Columns {
+        extended: cli.extended_statistics,
+        percentiles: cli.percentiles.clone(),
+    }
+}
+
+fn parse_percentile(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(percentile) if (0.0..=100.0).contains(&percentile) => Ok(percentile),
+        _ => Err(format!("{:?} is not a percentile between 0 and 100", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::default_output;
+    use super::expand_inputs;
+    use super::file_options;
+    use super::is_same_file;
+    use super::is_same_output;
+    use super::result_path;
+    use super::run_files;
+    use super::Aggregation;
+    use super::OnError;
+    use super::Solution;
+    use super::SolverOptions;
+
+    #[test]
+    fn test_expand_inputs() {
+        let inputs = |patterns: &[&str]| -> Result<Vec<String>, String> {
+            expand_inputs(
+                &patterns
+                    .iter()
+                    .map(|pattern: &&str| pattern.to_string())
+                    .collect::<Vec<String>>(),
+            )
+        };
+
+        assert_eq!(
+            Ok(vec![
+                String::from("resources/measurements_20.txt"),
+                String::from("resources/measurements_corrupted.txt"),
+                String::from("resources/measurements_malformed.txt"),
+            ]),
+            inputs(&[
+                "resources/measurements_20.txt",
+                "resources/measurements_*.txt"
+            ])
+        );
+        assert_eq!(Ok(vec![String::from("-")]), inputs(&["-"]));
+        assert_eq!(
+            Ok(vec![String::from("resources/missing.txt")]),
+            inputs(&["resources/missing.txt"])
+        );
+        assert!(inputs(&["resources/missing_*.txt"]).is_err());
+        assert!(inputs(&["resources/[.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_run_files() {
+        let options: SolverOptions = SolverOptions::default();
+        let shards: Vec<String> = vec![String::from("resources/measurements_20.txt"); 3];
+        let files = |solution: Solution, inputs: &[String]| -> Result<Aggregation, String> {
+            run_files(solution, inputs, &options, false)
+        };
+
+        for solution in [Solution::RayonFxHash, Solution::Mmap, Solution::Basic] {
+            let single: Aggregation = files(solution, &shards[..1]).unwrap();
+            let all: Aggregation = files(solution, &shards).unwrap();
+            assert_eq!(single.stations.len(), all.stations.len());
+            assert_eq!(
+                3 * single.stations["Hamburg"].count(),
+                all.stations["Hamburg"].count()
+            );
+
+            let mut inputs: Vec<String> = shards.clone();
+            inputs.insert(1, String::from("resources/measurements_malformed.txt"));
+            inputs.extend((1..=8).map(|index: usize| format!("resources/missing_{}.txt", index)));
+            for _ in 0..8 {
+                assert!(files(solution, &inputs)
+                    .unwrap_err()
+                    .starts_with("resources/measurements_malformed.txt: "));
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_output() {
+        assert_eq!(
+            "resources/results_mmap_20.txt",
+            default_output(
+                &[String::from("resources/measurements_20.txt")],
+                Solution::Mmap
+            )
+        );
+        assert_eq!(
+            "results_mmap.txt",
+            default_output(
+                &[String::from("shard_1.txt"), String::from("shard_2.txt")],
+                Solution::Mmap
+            )
+        );
+    }
+
+    #[test]
+    fn test_result_path() {
+        assert_eq!(
+            "resources/results_mmap_20.txt",
+            result_path("resources/measurements_20.txt", Solution::Mmap)
+        );
+        assert_eq!(
+            "measurements/results_rayonfxhash_measurements.txt",
+            result_path(
+                "measurements/measurements_measurements.txt",
+                Solution::RayonFxHash
+            )
+        );
+        assert_eq!(
+            "data/results_naive_input.txt",
+            result_path("data/input.txt", Solution::Naive)
+        );
+        assert_eq!(
+            "results_streaming_stdin.txt",
+            result_path("-", Solution::Streaming)
+        );
+    }
+
+    #[test]
+    fn test_is_same_file() {
+        assert!(is_same_file(
+            "resources/measurements_20.txt",
+            "resources/../resources/measurements_20.txt"
+        ));
+        assert!(!is_same_file(
+            "resources/measurements_20.txt",
+            "resources/measurements_malformed.txt"
+        ));
+        assert!(is_same_file(
+            "resources/missing.txt",
+            "resources/../resources/missing.txt"
+        ));
+        assert!(is_same_file("missing.txt", "./missing.txt"));
+        assert!(!is_same_file(
+            "resources/missing.txt",
+            "resources/missing.txt.1"
+        ));
+        assert!(!is_same_file("missing/missing.txt", "missing/missing.txt"));
+    }
+
+    #[test]
+    fn test_is_same_output() {
+        assert!(is_same_output("-", "-"));
+        assert!(is_same_output("results.txt", "./results.txt"));
+        assert!(!is_same_output("-", "results.txt"));
+        assert!(!is_same_output("results.txt", "results.part"));
+    }
+
+    #[test]
+    fn test_file_options() {
+        let options: SolverOptions = SolverOptions {
+            on_error: OnError::Quarantine(String::from("rejected.txt")),
+            ..SolverOptions::default()
+        };
 
-    let file: File = File::create(
-        cli.input_path.replace(
-            "measurements",
-            format!("results_{:?}", cli.solution)
-                .to_lowercase()
-                .as_str(),
-        ),
-    )
-    .unwrap_or_else(|_| panic!("Cannot create result file!"));
-    let mut writer: BufWriter<File> = BufWriter::new(file);
-    writeln!(writer, "{}", output).unwrap();
+        assert_eq!(options, file_options(&options, 1, 0));
+        assert_eq!(
+            OnError::Quarantine(String::from("rejected.txt.2")),
+            file_options(&options, 3, 1).on_error
+        );
+        assert_eq!(
+            SolverOptions::default(),
+            file_options(&SolverOptions::default(), 3, 1)
+        );
+    }
 }
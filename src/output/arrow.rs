@@ -0,0 +1,165 @@
+//! Export of the results as Arrow IPC or Parquet files, for analytics tools that read them natively.
+//! Only built with the `arrow` feature.
+//!
+//! Both hold a single record batch, one row per weather station sorted by name, with the columns:
+//!
+//! | Column    | Type      |
+//! |-----------|-----------|
+//! | `station` | `utf8`    |
+//! | `min`     | `float64` |
+//! | `max`     | `float64` |
+//! | `mean`    | `float64` |
+//! | `count`   | `uint64`  |
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::ArrayRef;
+use arrow_array::Float64Array;
+use arrow_array::RecordBatch;
+use arrow_array::StringArray;
+use arrow_array::UInt64Array;
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::ArrowError;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+
+use crate::solutions::aggregation::Aggregation;
+use crate::solutions::aggregation::Statistics;
+use crate::solutions::error::ObrcError;
+
+/// Schema of the exported results.
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("station", DataType::Utf8, false),
+        Field::new("min", DataType::Float64, false),
+        Field::new("max", DataType::Float64, false),
+        Field::new("mean", DataType::Float64, false),
+        Field::new("count", DataType::UInt64, false),
+    ])
+}
+
+/// The aggregation as a record batch, one row per weather station.
+pub fn record_batch(aggregation: &Aggregation) -> Result<RecordBatch, ObrcError> {
+    let stats = || aggregation.stations.values();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(aggregation.stations.keys())),
+        Arc::new(Float64Array::from_iter_values(stats().map(Statistics::min))),
+        Arc::new(Float64Array::from_iter_values(stats().map(Statistics::max))),
+        Arc::new(Float64Array::from_iter_values(
+            stats().map(Statistics::mean),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            stats().map(Statistics::count),
+        )),
+    ];
+    RecordBatch::try_new(Arc::new(schema()), columns).map_err(arrow_error)
+}
+
+/// Write the aggregation as an Arrow IPC file.
+pub fn write_ipc<W: Write>(aggregation: &Aggregation, writer: W) -> Result<(), ObrcError> {
+    let batch: RecordBatch = record_batch(aggregation)?;
+    let mut writer: FileWriter<W> =
+        FileWriter::try_new(writer, &batch.schema()).map_err(arrow_error)?;
+    writer.write(&batch).map_err(arrow_error)?;
+    writer.finish().map_err(arrow_error)
+}
+
+/// Write the aggregation as a Parquet file.
+pub fn write_parquet<W: Write + Send>(
+    aggregation: &Aggregation,
+    writer: W,
+) -> Result<(), ObrcError> {
+    let batch: RecordBatch = record_batch(aggregation)?;
+    let mut writer: ArrowWriter<W> =
+        ArrowWriter::try_new(writer, batch.schema(), None).map_err(parquet_error)?;
+    writer.write(&batch).map_err(parquet_error)?;
+    writer.close().map(|_| ()).map_err(parquet_error)
+}
+
+fn arrow_error(error: ArrowError) -> ObrcError {
+    ObrcError::Export(error.to_string())
+}
+
+fn parquet_error(error: ParquetError) -> ObrcError {
+    ObrcError::Export(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+    use std::fs::File;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use arrow_array::RecordBatch;
+    use arrow_ipc::reader::FileReader;
+    use arrow_schema::FieldRef;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::record_batch;
+    use super::write_ipc;
+    use super::write_parquet;
+    use super::Aggregation;
+    use super::Statistics;
+
+    fn aggregation() -> Aggregation {
+        let mut hamburg: Statistics = Statistics::default();
+        hamburg.update(120);
+        hamburg.update(-34);
+        let mut zurich: Statistics = Statistics::default();
+        zurich.update(-99);
+
+        [("Zürich", zurich), ("Hamburg", hamburg)]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_record_batch() {
+        let batch: RecordBatch = record_batch(&aggregation()).unwrap();
+
+        assert_eq!(2, batch.num_rows());
+        assert_eq!(
+            vec!["station", "min", "max", "mean", "count"],
+            batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|field: &FieldRef| field.name().as_str())
+                .collect::<Vec<&str>>()
+        );
+    }
+
+    #[test]
+    fn test_write_ipc() {
+        let mut bytes: Vec<u8> = Vec::new();
+        write_ipc(&aggregation(), &mut bytes).unwrap();
+
+        let batches: Vec<RecordBatch> = FileReader::try_new(Cursor::new(bytes), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(vec![record_batch(&aggregation()).unwrap()], batches);
+    }
+
+    #[test]
+    fn test_write_parquet() {
+        let path: PathBuf = temp_dir().join(format!("obrc_test_{}.parquet", std::process::id()));
+        write_parquet(&aggregation(), File::create(&path).unwrap()).unwrap();
+
+        let batches: Vec<RecordBatch> =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        remove_file(&path).unwrap();
+        assert_eq!(vec![record_batch(&aggregation()).unwrap()], batches);
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+
+use crate::solutions::aggregation::Aggregation;
+use crate::solutions::aggregation::Statistics;
+
+/// Layout of the rendered results, with the weather stations sorted by name.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Format {
+    /// Single `{Abha=-23.0/18.0/59.2, Abidjan=-16.2/26.0/67.3, ...}` line of `min/mean/max`, as in the original challenge.
+    #[default]
+    Official,
+    /// One `Abha=-23/59.2/18` line of `min/max/mean` per weather station.
+    Text,
+    /// Array of `{"name":"Abha","min":-23,"mean":18,"max":59.2,"count":5}` objects, one per weather station.
+    Json,
+    /// One JSON object per line and weather station, as in [`Format::Json`].
+    Ndjson,
+    /// `name,min,mean,max,count` header, then one row per weather station, quoted when needed (RFC 4180).
+    Csv,
+}
+
+/// Optional columns, rendered after the minimum, maximum and mean of every weather station.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Columns {
+    /// Standard deviation, variance and sum.
+    pub extended: bool,
+    /// Percentiles, e.g. `50.0` for the median. Requires the statistics to keep histograms.
+    pub percentiles: Vec<f64>,
+}
+
+/// Render the aggregation in the given format.
+pub fn format(aggregation: &Aggregation, format: Format) -> String {
+    format_with_columns(aggregation, format, &Columns::default())
+}
+
+/// Render the aggregation in the given format, with the given optional columns.
+pub fn format_with_columns(aggregation: &Aggregation, format: Format, columns: &Columns) -> String {
+    let mut output: String = String::new();
+
+    match format {
+        Format::Official => {
+            output.push('{');
+            for (index, (name, stats)) in aggregation.stations.iter().enumerate() {
+                if index > 0 {
+                    output.push_str(", ");
+                }
+                write!(
+                    output,
+                    "{}={:.1}/{:.1}/{:.1}",
+                    name,
+                    stats.min(),
+                    stats.mean(),
+                    stats.max()
+                )
+                .unwrap();
+                if columns.extended {
+                    write!(
+                        output,
+                        "/{:.1}/{:.1}/{:.1}",
+                        stats.std_dev().unwrap_or(f64::NAN),
+                        stats.variance().unwrap_or(f64::NAN),
+                        stats.sum()
+                    )
+                    .unwrap();
+                }
+                for percentile in &columns.percentiles {
+                    write!(
+                        output,
+                        "/{:.1}",
+                        stats.percentile(*percentile).unwrap_or(f64::NAN)
+                    )
+                    .unwrap();
+                }
+            }
+            output.push_str("}\n");
+        }
+        Format::Text => {
+            for (name, stats) in &aggregation.stations {
+                write!(
+                    output,
+                    "{}={}/{}/{}",
+                    name,
+                    stats.min(),
+                    stats.max(),
+                    stats.mean()
+                )
+                .unwrap();
+                if columns.extended {
+                    write_extended(&mut output, stats);
+                }
+                for percentile in &columns.percentiles {
+                    write!(
+                        output,
+                        "/{}",
+                        stats.percentile(*percentile).unwrap_or(f64::NAN)
+                    )
+                    .unwrap();
+                }
+                output.push('\n');
+            }
+        }
+        Format::Json => {
+            output.push('[');
+            for (index, (name, stats)) in aggregation.stations.iter().enumerate() {
+                output.push_str(if index > 0 { ",\n  " } else { "\n  " });
+                write_json_object(&mut output, name, stats, columns);
+            }
+            output.push_str(if aggregation.stations.is_empty() {
+                "]\n"
+            } else {
+                "\n]\n"
+            });
+        }
+        Format::Ndjson => {
+            for (name, stats) in &aggregation.stations {
+                write_json_object(&mut output, name, stats, columns);
+                output.push('\n');
+            }
+        }
+        Format::Csv => {
+            output.push_str("name,min,mean,max,count");
+            if columns.extended {
+                output.push_str(",std_dev,variance,sum");
+            }
+            for percentile in &columns.percentiles {
+                write!(output, ",p{}", percentile).unwrap();
+            }
+            output.push('\n');
+            for (name, stats) in &aggregation.stations {
+                write_csv_field(&mut output, name);
+                write!(
+                    output,
+                    ",{},{},{},{}",
+                    stats.min(),
+                    stats.mean(),
+                    stats.max(),
+                    stats.count()
+                )
+                .unwrap();
+                if columns.extended {
+                    for value in [stats.std_dev(), stats.variance()] {
+                        output.push(',');
+                        if let Some(value) = value {
+                            write!(output, "{}", value).unwrap();
+                        }
+                    }
+                    write!(output, ",{}", stats.sum()).unwrap();
+                }
+                for percentile in &columns.percentiles {
+                    output.push(',');
+                    if let Some(value) = stats.percentile(*percentile) {
+                        write!(output, "{}", value).unwrap();
+                    }
+                }
+                output.push('\n');
+            }
+        }
+    }
+
+    output
+}
+
+/// Write the statistics of a weather station as a single-line JSON object,
+/// with percentiles as `p<percentile>` keys, `null` without a histogram,
+/// and a `null` variance and standard deviation without a sum of squares.
+fn write_json_object(output: &mut String, name: &str, stats: &Statistics, columns: &Columns) {
+    output.push_str("{\"name\":");
+    write_json_string(output, name);
+    write!(
+        output,
+        ",\"min\":{},\"mean\":{},\"max\":{},\"count\":{}",
+        stats.min(),
+        stats.mean(),
+        stats.max(),
+        stats.count()
+    )
+    .unwrap();
+    if columns.extended {
+        for (key, value) in [("std_dev", stats.std_dev()), ("variance", stats.variance())] {
+            match value {
+                Some(value) => write!(output, ",\"{}\":{}", key, value).unwrap(),
+                None => write!(output, ",\"{}\":null", key).unwrap(),
+            }
+        }
+        write!(output, ",\"sum\":{}", stats.sum()).unwrap();
+    }
+    for percentile in &columns.percentiles {
+        match stats.percentile(*percentile) {
+            Some(value) => write!(output, ",\"p{}\":{}", percentile, value).unwrap(),
+            None => write!(output, ",\"p{}\":null", percentile).unwrap(),
+        }
+    }
+    output.push('}');
+}
+
+/// Write a JSON string literal, escaping quotes, backslashes and control characters.
+/// Other characters, non-ASCII included, are valid as they are.
+fn write_json_string(output: &mut String, value: &str) {
+    output.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                write!(output, "\\u{:04x}", character as u32).unwrap()
+            }
+            character => output.push(character),
+        }
+    }
+    output.push('"');
+}
+
+/// Write a CSV field, quoted and with its quotes doubled if it holds a comma, a quote or a line break.
+fn write_csv_field(output: &mut String, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        output.push('"');
+        output.push_str(&value.replace('"', "\"\""));
+        output.push('"');
+    } else {
+        output.push_str(value);
+    }
+}
+
+fn write_extended(output: &mut String, stats: &Statistics) {
+    write!(
+        output,
+        "/{}/{}/{}",
+        stats.std_dev().unwrap_or(f64::NAN),
+        stats.variance().unwrap_or(f64::NAN),
+        stats.sum()
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+    use super::format_with_columns;
+    use super::Aggregation;
+    use super::Columns;
+    use super::Format;
+    use super::Statistics;
+
+    fn aggregation() -> Aggregation {
+        let mut hamburg: Statistics = Statistics::with_sum_squares();
+        hamburg.update(120);
+        hamburg.update(-34);
+        let mut bulawayo: Statistics = Statistics::with_sum_squares();
+        bulawayo.update(89);
+        let mut palembang: Statistics = Statistics::with_sum_squares();
+        palembang.update(388);
+        palembang.update(-10);
+
+        [
+            ("Palembang", palembang),
+            ("Hamburg", hamburg),
+            ("Bulawayo", bulawayo),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_format_official() {
+        assert_eq!(
+            "{Bulawayo=8.9/8.9/8.9, Hamburg=-3.4/4.3/12.0, Palembang=-1.0/18.9/38.8}\n",
+            format(&aggregation(), Format::Official)
+        );
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            "Bulawayo=8.9/8.9/8.9\nHamburg=-3.4/12/4.3\nPalembang=-1/38.8/18.9\n",
+            format(&aggregation(), Format::Text)
+        );
+    }
+
+    #[test]
+    fn test_format_extended() {
+        let columns: Columns = Columns {
+            extended: true,
+            ..Columns::default()
+        };
+
+        assert_eq!(
+            "{Bulawayo=8.9/8.9/8.9/0.0/0.0/8.9, Hamburg=-3.4/4.3/12.0/7.7/59.3/8.6, Palembang=-1.0/18.9/38.8/19.9/396.0/37.8}\n",
+            format_with_columns(&aggregation(), Format::Official, &columns)
+        );
+        assert_eq!(
+            "Bulawayo=8.9/8.9/8.9/0/0/8.9\nHamburg=-3.4/12/4.3/7.7/59.3/8.6\nPalembang=-1/38.8/18.9/19.9/396/37.8\n",
+            format_with_columns(&aggregation(), Format::Text, &columns)
+        );
+    }
+
+    #[test]
+    fn test_format_percentiles() {
+        let mut hamburg: Statistics = Statistics {
+            acc_sum_squares: Some(0),
+            ..Statistics::with_histogram()
+        };
+        for temperature in [120, -34, 56, 78] {
+            hamburg.update(temperature);
+        }
+        let aggregation: Aggregation = [("Hamburg", hamburg)].into_iter().collect();
+        let columns: Columns = Columns {
+            extended: true,
+            percentiles: vec![50.0, 90.0],
+        };
+
+        assert_eq!(
+            "{Hamburg=-3.4/5.5/12.0/5.6/31.7/22.0/5.6/12.0}\n",
+            format_with_columns(&aggregation, Format::Official, &columns)
+        );
+        assert_eq!(
+            "Hamburg=-3.4/12/5.5/5.6/31.7/22/5.6/12\n",
+            format_with_columns(&aggregation, Format::Text, &columns)
+        );
+        let mut bulawayo: Statistics = Statistics::default();
+        bulawayo.update(89);
+        assert_eq!(
+            "{Bulawayo=8.9/8.9/8.9/NaN}\n",
+            format_with_columns(
+                &[("Bulawayo", bulawayo)].into_iter().collect(),
+                Format::Official,
+                &Columns {
+                    percentiles: vec![50.0],
+                    ..Columns::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            concat!(
+                "[\n",
+                "  {\"name\":\"Bulawayo\",\"min\":8.9,\"mean\":8.9,\"max\":8.9,\"count\":1},\n",
+                "  {\"name\":\"Hamburg\",\"min\":-3.4,\"mean\":4.3,\"max\":12,\"count\":2},\n",
+                "  {\"name\":\"Palembang\",\"min\":-1,\"mean\":18.9,\"max\":38.8,\"count\":2}\n",
+                "]\n"
+            ),
+            format(&aggregation(), Format::Json)
+        );
+        assert_eq!(
+            concat!(
+                "{\"name\":\"Bulawayo\",\"min\":8.9,\"mean\":8.9,\"max\":8.9,\"count\":1}\n",
+                "{\"name\":\"Hamburg\",\"min\":-3.4,\"mean\":4.3,\"max\":12,\"count\":2}\n",
+                "{\"name\":\"Palembang\",\"min\":-1,\"mean\":18.9,\"max\":38.8,\"count\":2}\n"
+            ),
+            format(&aggregation(), Format::Ndjson)
+        );
+    }
+
+    #[test]
+    fn test_format_csv() {
+        assert_eq!(
+            concat!(
+                "name,min,mean,max,count\n",
+                "Bulawayo,8.9,8.9,8.9,1\n",
+                "Hamburg,-3.4,4.3,12,2\n",
+                "Palembang,-1,18.9,38.8,2\n"
+            ),
+            format(&aggregation(), Format::Csv)
+        );
+    }
+
+    #[test]
+    fn test_format_columns_machine_readable() {
+        let mut hamburg: Statistics = Statistics {
+            acc_sum_squares: Some(0),
+            ..Statistics::with_histogram()
+        };
+        for temperature in [120, -34, 56, 78] {
+            hamburg.update(temperature);
+        }
+        let mut bulawayo: Statistics = Statistics::default();
+        bulawayo.update(89);
+        let aggregation: Aggregation = [("Hamburg", hamburg), ("Bulawayo", bulawayo)]
+            .into_iter()
+            .collect();
+        let columns: Columns = Columns {
+            extended: true,
+            percentiles: vec![50.0, 99.9],
+        };
+
+        assert_eq!(
+            concat!(
+                "{\"name\":\"Bulawayo\",\"min\":8.9,\"mean\":8.9,\"max\":8.9,\"count\":1,",
+                "\"std_dev\":null,\"variance\":null,\"sum\":8.9,\"p50\":null,\"p99.9\":null}\n",
+                "{\"name\":\"Hamburg\",\"min\":-3.4,\"mean\":5.5,\"max\":12,\"count\":4,",
+                "\"std_dev\":5.6,\"variance\":31.7,\"sum\":22,\"p50\":5.6,\"p99.9\":12}\n"
+            ),
+            format_with_columns(&aggregation, Format::Ndjson, &columns)
+        );
+        assert_eq!(
+            concat!(
+                "name,min,mean,max,count,std_dev,variance,sum,p50,p99.9\n",
+                "Bulawayo,8.9,8.9,8.9,1,,,8.9,,\n",
+                "Hamburg,-3.4,5.5,12,4,5.6,31.7,22,5.6,12\n"
+            ),
+            format_with_columns(&aggregation, Format::Csv, &columns)
+        );
+    }
+
+    #[test]
+    fn test_format_escaping() {
+        let mut stats: Statistics = Statistics::default();
+        stats.update(10);
+        let aggregation: Aggregation = [
+            ("Say \"Cheese\", Inc.", stats.clone()),
+            ("Back\\slash\ttab\u{1}", stats.clone()),
+            ("Zürich", stats.clone()),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            concat!(
+                "{\"name\":\"Back\\\\slash\\ttab\\u0001\",\"min\":1,\"mean\":1,\"max\":1,\"count\":1}\n",
+                "{\"name\":\"Say \\\"Cheese\\\", Inc.\",\"min\":1,\"mean\":1,\"max\":1,\"count\":1}\n",
+                "{\"name\":\"Zürich\",\"min\":1,\"mean\":1,\"max\":1,\"count\":1}\n"
+            ),
+            format(&aggregation, Format::Ndjson)
+        );
+        assert_eq!(
+            concat!(
+                "name,min,mean,max,count\n",
+                "Back\\slash\ttab\u{1},1,1,1,1\n",
+                "\"Say \"\"Cheese\"\", Inc.\",1,1,1,1\n",
+                "Zürich,1,1,1,1\n"
+            ),
+            format(&aggregation, Format::Csv)
+        );
+    }
+
+    #[test]
+    fn test_format_empty() {
+        assert_eq!("{}\n", format(&Aggregation::default(), Format::Official));
+        assert_eq!("", format(&Aggregation::default(), Format::Text));
+        assert_eq!("[]\n", format(&Aggregation::default(), Format::Json));
+        assert_eq!("", format(&Aggregation::default(), Format::Ndjson));
+        assert_eq!(
+            "name,min,mean,max,count\n",
+            format(&Aggregation::default(), Format::Csv)
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+
+use crate::utils::rounding::round_one_digit_precision;
+
+/// Lowest temperature allowed by the challenge, in tenths of a degree.
+pub const MIN_TEMPERATURE: i64 = -999;
+
+/// Highest temperature allowed by the challenge, in tenths of a degree.
+pub const MAX_TEMPERATURE: i64 = 999;
+
+/// Number of distinct temperatures allowed by the challenge, one histogram bucket each.
+pub const BUCKETS: usize = (MAX_TEMPERATURE - MIN_TEMPERATURE + 1) as usize;
+
+/// Precision of the percentiles, as the number of steps per percent: one millionth of a percent.
+pub const PERCENTILE_SCALE: u128 = 1_000_000;
+
+/// Counting histogram of the temperatures of a weather station, one bucket per tenth of a degree.
+/// Gives exact percentiles, and merges exactly by adding up the buckets.
+/// Takes `BUCKETS * 8` bytes, about 15.6 KiB.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub(crate) buckets: Box<[u64; BUCKETS]>,
+}
+
+impl Histogram {
+    /// Count a temperature, in tenths of a degree.
+    /// Temperatures outside of the challenge's range are counted in the first or last bucket.
+    #[inline]
+    pub fn add(&mut self, temperature: i64, count: u64) {
+        let index: i64 = temperature.clamp(MIN_TEMPERATURE, MAX_TEMPERATURE) - MIN_TEMPERATURE;
+        self.buckets[index as usize] += count;
+    }
+
+    /// Combine the buckets of another histogram into this one.
+    pub fn merge(&mut self, other: &Histogram) {
+        self.buckets
+            .iter_mut()
+            .zip(other.buckets.iter())
+            .for_each(|(bucket, other_bucket)| *bucket += *other_bucket);
+    }
+
+    /// Non-empty buckets, as temperatures in tenths of a degree and their counts.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| (index as i64 + MIN_TEMPERATURE, *count))
+    }
+
+    /// Nearest-rank percentile, in tenths of a degree: the lowest temperature
+    /// such that at least `percentile`% of the counted temperatures are lower or equal.
+    /// The rank is computed in integers, with the percentile rounded to [`PERCENTILE_SCALE`],
+    /// as floating-point products like `0.07 * 100` are not exact.
+    fn percentile(&self, percentile: f64) -> Option<i64> {
+        let total: u64 = self.buckets.iter().sum();
+        let scaled_percentile: u128 = (percentile * PERCENTILE_SCALE as f64).round() as u128;
+        let rank: u64 =
+            ((scaled_percentile * total as u128).div_ceil(100 * PERCENTILE_SCALE) as u64).max(1);
+        let mut cumulative: u64 = 0;
+        self.iter().find_map(|(temperature, count)| {
+            cumulative += count;
+            (cumulative >= rank).then_some(temperature)
+        })
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: Box::new([0; BUCKETS]),
+        }
+    }
+}
+
+/// Statistics of a single weather station.
+/// Temperatures are accumulated in tenths of a degree, so merging partial results is exact.
+/// The sum of squares behind the variance and the histogram behind the percentiles
+/// are only kept on request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    pub(crate) curr_min: i64,
+    pub(crate) curr_max: i64,
+    pub(crate) acc_sum: i64,
+    pub(crate) acc_sum_squares: Option<u64>,
+    pub(crate) count: u64,
+    pub(crate) histogram: Option<Histogram>,
+}
+
+impl Statistics {
+    /// Empty statistics that also keep a histogram of the temperatures, for exact percentiles.
+    pub fn with_histogram() -> Statistics {
+        Statistics {
+            histogram: Some(Histogram::default()),
+            ..Statistics::default()
+        }
+    }
+
+    /// Empty statistics that also keep the sum of squares of the temperatures,
+    /// for the variance and standard deviation.
+    pub fn with_sum_squares() -> Statistics {
+        Statistics {
+            acc_sum_squares: Some(0),
+            ..Statistics::default()
+        }
+    }
+
+    /// Add a temperature, in tenths of a degree.
+    #[inline]
+    pub fn update(&mut self, temperature: i64) {
+        self.curr_min = self.curr_min.min(temperature);
+        self.curr_max = self.curr_max.max(temperature);
+        self.acc_sum += temperature;
+        self.count += 1;
+        if let Some(acc_sum_squares) = &mut self.acc_sum_squares {
+            *acc_sum_squares += temperature.unsigned_abs().pow(2);
+        }
+        if let Some(histogram) = &mut self.histogram {
+            histogram.add(temperature, 1);
+        }
+    }
+
+    /// Combine the statistics of another partial result into this one.
+    /// The sum of squares and the histogram are only kept if both sides have one, or one side is empty.
+    #[inline]
+    pub fn merge(&mut self, other: &Statistics) {
+        match (&mut self.acc_sum_squares, other.acc_sum_squares) {
+            (Some(acc_sum_squares), Some(other_sum_squares)) => {
+                *acc_sum_squares += other_sum_squares
+            }
+            (None, Some(other_sum_squares)) if self.count == 0 => {
+                self.acc_sum_squares = Some(other_sum_squares)
+            }
+            (Some(_), None) if other.count == 0 => {}
+            _ => self.acc_sum_squares = None,
+        }
+        match (&mut self.histogram, &other.histogram) {
+            (Some(histogram), Some(other_histogram)) => histogram.merge(other_histogram),
+            (None, Some(other_histogram)) if self.count == 0 => {
+                self.histogram = Some(other_histogram.clone())
+            }
+            (Some(_), None) if other.count == 0 => {}
+            _ => self.histogram = None,
+        }
+        self.curr_min = self.curr_min.min(other.curr_min);
+        self.curr_max = self.curr_max.max(other.curr_max);
+        self.acc_sum += other.acc_sum;
+        self.count += other.count;
+    }
+
+    /// Minimum temperature, in degrees.
+    pub fn min(&self) -> f64 {
+        round_one_digit_precision(self.curr_min as f64 / 10.0)
+    }
+
+    /// Maximum temperature, in degrees.
+    pub fn max(&self) -> f64 {
+        round_one_digit_precision(self.curr_max as f64 / 10.0)
+    }
+
+    /// Mean temperature, in degrees.
+    pub fn mean(&self) -> f64 {
+        round_one_digit_precision(self.acc_sum as f64 / (10.0 * self.count as f64))
+    }
+
+    /// Number of measurements.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sum of all temperatures, in degrees.
+    pub fn sum(&self) -> f64 {
+        round_one_digit_precision(self.acc_sum as f64 / 10.0)
+    }
+
+    /// Population variance of the temperatures, in squared degrees.
+    /// Returns `None` if no sum of squares was kept.
+    pub fn variance(&self) -> Option<f64> {
+        self.exact_variance().map(round_one_digit_precision)
+    }
+
+    /// Population standard deviation of the temperatures, in degrees.
+    /// Returns `None` if no sum of squares was kept.
+    pub fn std_dev(&self) -> Option<f64> {
+        self.exact_variance()
+            .map(|variance: f64| round_one_digit_precision(variance.sqrt()))
+    }
+
+    /// Percentile of the temperatures, in degrees, e.g. `50.0` for the median.
+    /// Returns `None` if no histogram was kept.
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        self.histogram
+            .as_ref()?
+            .percentile(percentile)
+            .map(|temperature: i64| round_one_digit_precision(temperature as f64 / 10.0))
+    }
+
+    /// Histogram of the temperatures, if kept.
+    pub fn histogram(&self) -> Option<&Histogram> {
+        self.histogram.as_ref()
+    }
+
+    /// `(n * Σx² - (Σx)²) / n²`, with the numerator computed exactly in integers.
+    fn exact_variance(&self) -> Option<f64> {
+        let count: i128 = self.count as i128;
+        let numerator: i128 = count * self.acc_sum_squares? as i128 - (self.acc_sum as i128).pow(2);
+        Some(numerator as f64 / (count.pow(2) as f64 * 100.0))
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self {
+            curr_min: i64::MAX,
+            curr_max: i64::MIN,
+            acc_sum: 0,
+            acc_sum_squares: None,
+            count: 0,
+            histogram: None,
+        }
+    }
+}
+
+/// The result of the One Billion Row Challenge: the statistics of every weather station.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Aggregation {
+    pub stations: BTreeMap<String, Statistics>,
+    /// Number of malformed lines skipped or quarantined, see [`OnError`](super::solver::OnError).
+    pub rejected: u64,
+}
+
+impl Aggregation {
+    /// Combine another partial result into this one.
+    pub fn merge(&mut self, other: Aggregation) {
+        for (name, stats) in other.stations {
+            self.stations.entry(name).or_default().merge(&stats);
+        }
+        self.rejected += other.rejected;
+    }
+
+    /// Names of the weather stations whose statistics differ from another result,
+    /// or that only one of the results has, in order.
+    pub fn differences<'a>(&'a self, other: &'a Aggregation) -> Vec<&'a str> {
+        let mut names: Vec<&str> = self
+            .stations
+            .iter()
+            .filter(|(name, stats)| other.stations.get(*name) != Some(*stats))
+            .chain(
+                other
+                    .stations
+                    .iter()
+                    .filter(|(name, _)| !self.stations.contains_key(*name)),
+            )
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl<S> FromIterator<(S, Statistics)> for Aggregation
+where
+    S: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (S, Statistics)>>(iter: I) -> Self {
+        let mut aggregation: Aggregation = Aggregation::default();
+        for (name, stats) in iter {
+            aggregation
+                .stations
+                .entry(name.into())
+                .or_default()
+                .merge(&stats);
+        }
+        aggregation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Aggregation;
+    use super::Statistics;
+
+    #[test]
+    fn test_differences() {
+        let stats = |temperatures: &[i64]| -> Statistics {
+            let mut stats: Statistics = Statistics::default();
+            temperatures
+                .iter()
+                .for_each(|temperature: &i64| stats.update(*temperature));
+            stats
+        };
+        let aggregation: Aggregation = [
+            ("Abha", stats(&[10, 20])),
+            ("Cracow", stats(&[30])),
+            ("Hamburg", stats(&[-5])),
+        ]
+        .into_iter()
+        .collect();
+        let other: Aggregation = [
+            ("Abha", stats(&[10, 20])),
+            ("Bulawayo", stats(&[30])),
+            ("Cracow", stats(&[31])),
+        ]
+        .into_iter()
+        .collect();
+
+        assert!(aggregation.differences(&aggregation).is_empty());
+        assert_eq!(
+            vec!["Bulawayo", "Cracow", "Hamburg"],
+            aggregation.differences(&other)
+        );
+        assert_eq!(
+            vec!["Bulawayo", "Cracow", "Hamburg"],
+            other.differences(&aggregation)
+        );
+    }
+
+    #[test]
+    fn test_extended_statistics() {
+        let mut stats: Statistics = Statistics::with_sum_squares();
+        let mut without_squares: Statistics = Statistics::default();
+        for temperature in [20, 40, 40, 40, 50, 50, 70, 90] {
+            stats.update(temperature);
+            without_squares.update(temperature);
+        }
+
+        assert_eq!(8, stats.count());
+        assert_eq!(5.0, stats.mean());
+        assert_eq!(40.0, stats.sum());
+        assert_eq!(Some(4.0), stats.variance());
+        assert_eq!(Some(2.0), stats.std_dev());
+        assert_eq!(None, without_squares.variance());
+        assert_eq!(None, without_squares.std_dev());
+
+        let mut merged: Statistics = Statistics::default();
+        merged.merge(&stats);
+        assert_eq!(Some(4.0), merged.variance());
+        merged.merge(&without_squares);
+        assert_eq!(None, merged.variance());
+    }
+
+    #[test]
+    fn test_percentiles_exact_ranks() {
+        for total in [100, 200] {
+            let mut stats: Statistics = Statistics::with_histogram();
+            for temperature in 1..=total {
+                stats.update(temperature);
+            }
+            for percentile in [7, 14, 28, 55, 56] {
+                let rank: i64 = percentile * total / 100;
+                assert_eq!(
+                    Some(rank as f64 / 10.0),
+                    stats.percentile(percentile as f64),
+                    "p{} of {}",
+                    percentile,
+                    total
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut stats: Statistics = Statistics::with_histogram();
+        for temperature in (1..=100).rev() {
+            stats.update(temperature);
+        }
+
+        assert_eq!(Some(0.1), stats.percentile(0.0));
+        assert_eq!(Some(0.1), stats.percentile(1.0));
+        assert_eq!(Some(5.0), stats.percentile(50.0));
+        assert_eq!(Some(5.1), stats.percentile(50.5));
+        assert_eq!(Some(9.0), stats.percentile(90.0));
+        assert_eq!(Some(9.9), stats.percentile(99.0));
+        assert_eq!(Some(10.0), stats.percentile(100.0));
+        assert_eq!(None, Statistics::default().percentile(50.0));
+    }
+
+    #[test]
+    fn test_percentiles_extremes() {
+        let mut stats: Statistics = Statistics::with_histogram();
+        for temperature in [-999, -999, 999] {
+            stats.update(temperature);
+        }
+
+        assert_eq!(Some(-99.9), stats.percentile(50.0));
+        assert_eq!(Some(99.9), stats.percentile(99.0));
+    }
+
+    #[test]
+    fn test_merge_is_exact() {
+        let temperatures: Vec<i64> = (-999..=999).step_by(7).collect();
+        let mut expected: Statistics = Statistics::with_histogram();
+        temperatures
+            .iter()
+            .for_each(|temperature: &i64| expected.update(*temperature));
+
+        for chunk_size in [1, 2, 13, 100, temperatures.len()] {
+            let mut merged: Statistics = Statistics::default();
+            for chunk in temperatures.chunks(chunk_size) {
+                let mut stats: Statistics = Statistics::with_histogram();
+                chunk
+                    .iter()
+                    .for_each(|temperature: &i64| stats.update(*temperature));
+                merged.merge(&stats);
+            }
+            assert_eq!(expected, merged);
+        }
+    }
+}
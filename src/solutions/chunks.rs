@@ -0,0 +1,143 @@
+use std::fs::File;
+use std::thread::scope;
+use std::thread::ScopedJoinHandle;
+
+use super::error::ObrcError;
+
+use memmap2::Mmap;
+
+/// Memory-map the input file, for the solvers that split it into chunks.
+pub(crate) fn map_input(input_path: &str) -> Result<Mmap, ObrcError> {
+    let file: File = File::open(input_path)?;
+    // SAFETY: the mapping is read-only and only lives for the duration of the solver call;
+    // the input file is not expected to be modified concurrently.
+    Ok(unsafe { Mmap::map(&file)? })
+}
+
+/// Split the input into at most `threads` chunks with [`split_chunks`], and solve each of them
+/// on its own thread. Returns the results in the order of the chunks.
+pub(crate) fn solve_chunks<'a, T, F>(
+    input: &'a [u8],
+    threads: usize,
+    solve_chunk: F,
+) -> Vec<Result<T, ObrcError>>
+where
+    T: Send,
+    F: Fn(&'a [u8]) -> Result<T, ObrcError> + Sync,
+{
+    let chunks: Vec<&[u8]> = split_chunks(input, threads);
+
+    scope(|s| {
+        chunks
+            .iter()
+            .map(|chunk: &&[u8]| s.spawn(|| solve_chunk(chunk)))
+            .collect::<Vec<ScopedJoinHandle<'_, _>>>()
+            .into_iter()
+            .map(|handle: ScopedJoinHandle<'_, _>| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Split the input into at most `count` chunks of similar size, each ending right after a newline
+/// (or at the end of the input).
+pub(crate) fn split_chunks(input: &[u8], count: usize) -> Vec<&[u8]> {
+    let chunk_size: usize = input.len().div_ceil(count.max(1)).max(1);
+    let mut chunks: Vec<&[u8]> = Vec::with_capacity(count);
+    let mut start: usize = 0;
+
+    while start < input.len() {
+        let end: usize = (start + chunk_size).min(input.len());
+        let end: usize = input[end - 1..]
+            .iter()
+            .position(|byte: &u8| *byte == b'\n')
+            .map_or(input.len(), |position: usize| end + position);
+        chunks.push(&input[start..end]);
+        start = end;
+    }
+
+    chunks
+}
+
+/// The name of a weather station, from bytes that were checked to be UTF-8 when first seen.
+pub(crate) fn station_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read;
+
+    use super::split_chunks;
+    use crate::solutions::aggregation::Aggregation;
+    use crate::solutions::error::ObrcError;
+    use crate::solutions::solution_basic::SolutioBasic;
+    use crate::solutions::solution_mmap;
+    use crate::solutions::solution_open_addressing;
+    use crate::solutions::solver::Solver;
+    use crate::solutions::solver::SolverOptions;
+
+    type SolveFn = fn(&[u8], usize, &SolverOptions) -> Result<Aggregation, ObrcError>;
+
+    const SOLVERS: [(&str, SolveFn); 2] = [
+        ("mmap", solution_mmap::solve),
+        ("open_addressing", solution_open_addressing::solve),
+    ];
+
+    #[test]
+    fn test_solution_chunks() {
+        let input: Vec<u8> = read("resources/measurements_20.txt").unwrap();
+        let expected: Aggregation =
+            SolutioBasic::solve_obrc("resources/measurements_20.txt").unwrap();
+
+        for (name, solve) in SOLVERS {
+            for threads in 1..=32 {
+                assert_eq!(
+                    expected,
+                    solve(&input, threads, &SolverOptions::default()).unwrap(),
+                    "{} with {} threads",
+                    name,
+                    threads
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_solution_chunks_histograms() {
+        let input: Vec<u8> = read("resources/measurements_20.txt").unwrap();
+        let options: SolverOptions = SolverOptions {
+            histograms: true,
+            ..SolverOptions::default()
+        };
+        let expected: Aggregation =
+            SolutioBasic::solve_obrc_with_options("resources/measurements_20.txt", &options)
+                .unwrap();
+
+        for (name, solve) in SOLVERS {
+            for threads in 1..=32 {
+                assert_eq!(
+                    expected,
+                    solve(&input, threads, &options).unwrap(),
+                    "{} with {} threads",
+                    name,
+                    threads
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let input: &[u8] = b"a;1.0\nbb;2.0\nccc;3.0\nd;4.0";
+
+        for count in 1..=input.len() + 1 {
+            let chunks: Vec<&[u8]> = split_chunks(input, count);
+            assert!(chunks.len() <= count);
+            assert_eq!(input, chunks.concat());
+            chunks[..chunks.len() - 1]
+                .iter()
+                .for_each(|chunk: &&[u8]| assert_eq!(Some(&b'\n'), chunk.last()));
+        }
+        assert!(split_chunks(b"", 4).is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use std::io;
+
+/// Errors that can occur while solving the One Billion Row Challenge.
+#[derive(Debug)]
+pub enum ObrcError {
+    /// The input could not be read.
+    Io(io::Error),
+    /// A line is not a valid `<station name>;<temperature>` measurement.
+    MalformedLine {
+        /// The line, invalid UTF-8 replaced.
+        line: String,
+        /// Offset of the line in the input, in bytes.
+        offset: u64,
+        /// Number of the line in the input, from 1.
+        line_number: u64,
+    },
+    /// The input holds more distinct weather stations than the solver can track.
+    TooManyStations(usize),
+    /// A partial result is not in the expected binary format.
+    InvalidPartial(String),
+    /// A weather stations file is not a valid `name;mean[;stddev]` catalog, for these reasons.
+    InvalidStationsFile(Vec<String>),
+    /// The results could not be exported, e.g. as Arrow IPC or Parquet, for this reason.
+    Export(String),
+}
+
+impl Display for ObrcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ObrcError::Io(error) => write!(f, "I/O error: {}", error),
+            ObrcError::MalformedLine {
+                line,
+                offset,
+                line_number,
+            } => write!(
+                f,
+                "malformed line {} at byte {}: {:?}",
+                line_number, offset, line
+            ),
+            ObrcError::TooManyStations(max) => {
+                write!(f, "more than {} distinct weather stations", max)
+            }
+            ObrcError::InvalidPartial(reason) => write!(f, "invalid partial result: {}", reason),
+            ObrcError::InvalidStationsFile(reasons) => {
+                write!(f, "invalid stations file:")?;
+                reasons
+                    .iter()
+                    .try_for_each(|reason: &String| write!(f, "\n  {}", reason))
+            }
+            ObrcError::Export(reason) => write!(f, "cannot export the results: {}", reason),
+        }
+    }
+}
+
+impl Error for ObrcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObrcError::Io(error) => Some(error),
+            ObrcError::MalformedLine { .. }
+            | ObrcError::TooManyStations(_)
+            | ObrcError::InvalidPartial(_)
+            | ObrcError::InvalidStationsFile(_)
+            | ObrcError::Export(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObrcError {
+    fn from(error: io::Error) -> Self {
+        ObrcError::Io(error)
+    }
+}
@@ -0,0 +1,422 @@
+//! Binary format of partial results, so that the aggregations of several processes or machines,
+//! each over a shard of the measurements, can be combined into the final result.
+//!
+//! All integers are little-endian:
+//!
+//! | Field    | Type      | Description                                                     |
+//! |----------|-----------|-----------------------------------------------------------------|
+//! | magic    | `[u8; 8]` | `OBRCPART`                                                      |
+//! | version  | `u16`     | [`VERSION`]                                                     |
+//! | rejected | `u64`     | Number of malformed lines skipped or quarantined                |
+//! | count    | `u32`     | Number of weather stations, each name at most once              |
+//! | stations | `count ×` | `name_len: u16`, `name: [u8; name_len]` (UTF-8), then the       |
+//! |          |           | `min: i64`, `max: i64`, `sum: i64` (tenths of a degree)         |
+//! |          |           | and `count: u64` of the station, then `sum_squares: u8`,        |
+//! |          |           | 1 if a `sum_squares: u64` (hundredths) follows and 0 otherwise, |
+//! |          |           | and `histogram: u8`, 1 if a histogram follows and 0 otherwise   |
+//! |          |           | histogram: `buckets: u16`, then `buckets ×` the non-empty       |
+//! |          |           | `temperature: i16` (tenths of a degree) and their `count: u64`  |
+//! | checksum | `u64`     | 64-bit FNV-1a hash of every preceding byte, magic included      |
+
+use std::io::Read;
+use std::io::Write;
+
+use super::aggregation::Aggregation;
+use super::aggregation::Histogram;
+use super::aggregation::Statistics;
+use super::aggregation::MAX_TEMPERATURE;
+use super::aggregation::MIN_TEMPERATURE;
+use super::error::ObrcError;
+
+/// First bytes of every partial result.
+pub const MAGIC: &[u8; 8] = b"OBRCPART";
+
+/// Version of the format written by [`write_partial`].
+pub const VERSION: u16 = 1;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Write the aggregation as a partial result.
+pub fn write_partial<W: Write>(aggregation: &Aggregation, mut writer: W) -> Result<(), ObrcError> {
+    let mut bytes: Vec<u8> = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&aggregation.rejected.to_le_bytes());
+    bytes.extend_from_slice(&(aggregation.stations.len() as u32).to_le_bytes());
+
+    for (name, stats) in &aggregation.stations {
+        let name_len: u16 = u16::try_from(name.len())
+            .map_err(|_| ObrcError::InvalidPartial(format!("station name too long: {:?}", name)))?;
+        bytes.extend_from_slice(&name_len.to_le_bytes());
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&stats.curr_min.to_le_bytes());
+        bytes.extend_from_slice(&stats.curr_max.to_le_bytes());
+        bytes.extend_from_slice(&stats.acc_sum.to_le_bytes());
+        bytes.extend_from_slice(&stats.count.to_le_bytes());
+        match stats.acc_sum_squares {
+            Some(acc_sum_squares) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&acc_sum_squares.to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+        match &stats.histogram {
+            Some(histogram) => {
+                let buckets: Vec<(i64, u64)> = histogram.iter().collect();
+                bytes.push(1);
+                bytes.extend_from_slice(&(buckets.len() as u16).to_le_bytes());
+                for (temperature, count) in buckets {
+                    bytes.extend_from_slice(&(temperature as i16).to_le_bytes());
+                    bytes.extend_from_slice(&count.to_le_bytes());
+                }
+            }
+            None => bytes.push(0),
+        }
+    }
+
+    bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read a partial result written by [`write_partial`].
+/// Fails on inconsistent contents too, e.g. a minimum above the maximum
+/// or a histogram that does not count every measurement of its station.
+pub fn read_partial<R: Read>(mut reader: R) -> Result<Aggregation, ObrcError> {
+    let mut bytes: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(ObrcError::InvalidPartial(String::from(
+            "missing magic header",
+        )));
+    }
+    if bytes.len() < MAGIC.len() + 2 + 8 + 4 + 8 {
+        return Err(ObrcError::InvalidPartial(String::from("truncated header")));
+    }
+    let (content, expected_checksum) = bytes.split_at(bytes.len() - 8);
+    if checksum(content) != u64::from_le_bytes(expected_checksum.try_into().unwrap()) {
+        return Err(ObrcError::InvalidPartial(String::from("checksum mismatch")));
+    }
+
+    let mut cursor: Cursor<'_> = Cursor {
+        bytes: &content[MAGIC.len()..],
+    };
+    let version: u16 = u16::from_le_bytes(cursor.take()?);
+    if version != VERSION {
+        return Err(ObrcError::InvalidPartial(format!(
+            "unsupported version {}",
+            version
+        )));
+    }
+
+    let mut aggregation: Aggregation = Aggregation {
+        rejected: u64::from_le_bytes(cursor.take()?),
+        ..Aggregation::default()
+    };
+    let count: u32 = u32::from_le_bytes(cursor.take()?);
+    for _ in 0..count {
+        let name_len: u16 = u16::from_le_bytes(cursor.take()?);
+        let name: String = String::from_utf8(cursor.take_slice(name_len as usize)?.to_vec())
+            .map_err(|_| ObrcError::InvalidPartial(String::from("station name is not UTF-8")))?;
+        let stats: Statistics = Statistics {
+            curr_min: i64::from_le_bytes(cursor.take()?),
+            curr_max: i64::from_le_bytes(cursor.take()?),
+            acc_sum: i64::from_le_bytes(cursor.take()?),
+            count: u64::from_le_bytes(cursor.take()?),
+            acc_sum_squares: read_sum_squares(&mut cursor)?,
+            histogram: read_histogram(&mut cursor)?,
+        };
+        validate(&name, &stats)?;
+        if aggregation.stations.contains_key(&name) {
+            return Err(ObrcError::InvalidPartial(format!(
+                "duplicate station {:?}",
+                name
+            )));
+        }
+        aggregation.stations.insert(name, stats);
+    }
+
+    if !cursor.bytes.is_empty() {
+        return Err(ObrcError::InvalidPartial(String::from(
+            "trailing bytes after the last station",
+        )));
+    }
+
+    Ok(aggregation)
+}
+
+fn validate(name: &str, stats: &Statistics) -> Result<(), ObrcError> {
+    if stats.count == 0 || stats.curr_min > stats.curr_max {
+        return Err(ObrcError::InvalidPartial(format!(
+            "station {:?} has no measurements or a minimum above its maximum",
+            name
+        )));
+    }
+    if let Some(histogram) = &stats.histogram {
+        let counted: u64 = histogram.iter().map(|(_, count): (i64, u64)| count).sum();
+        if counted != stats.count {
+            return Err(ObrcError::InvalidPartial(format!(
+                "histogram of station {:?} counts {} measurements instead of {}",
+                name, counted, stats.count
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn read_sum_squares(cursor: &mut Cursor<'_>) -> Result<Option<u64>, ObrcError> {
+    match cursor.take::<1>()? {
+        [0] => Ok(None),
+        [1] => Ok(Some(u64::from_le_bytes(cursor.take()?))),
+        [flag] => Err(ObrcError::InvalidPartial(format!(
+            "invalid sum of squares flag {}",
+            flag
+        ))),
+    }
+}
+
+fn read_histogram(cursor: &mut Cursor<'_>) -> Result<Option<Histogram>, ObrcError> {
+    match cursor.take::<1>()? {
+        [0] => return Ok(None),
+        [1] => {}
+        [flag] => {
+            return Err(ObrcError::InvalidPartial(format!(
+                "invalid histogram flag {}",
+                flag
+            )))
+        }
+    }
+
+    let mut histogram: Histogram = Histogram::default();
+    let buckets: u16 = u16::from_le_bytes(cursor.take()?);
+    for _ in 0..buckets {
+        let temperature: i64 = i16::from_le_bytes(cursor.take()?) as i64;
+        if !(MIN_TEMPERATURE..=MAX_TEMPERATURE).contains(&temperature) {
+            return Err(ObrcError::InvalidPartial(format!(
+                "histogram temperature {} out of range",
+                temperature
+            )));
+        }
+        histogram.add(temperature, u64::from_le_bytes(cursor.take()?));
+    }
+    Ok(Some(histogram))
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash: u64, byte: &u8| {
+        (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn take_slice(&mut self, len: usize) -> Result<&'a [u8], ObrcError> {
+        if self.bytes.len() < len {
+            return Err(ObrcError::InvalidPartial(String::from(
+                "truncated stations",
+            )));
+        }
+        let (slice, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(slice)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ObrcError> {
+        Ok(self.take_slice(N)?.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read;
+
+    use super::checksum;
+    use super::read_partial;
+    use super::write_partial;
+    use super::Aggregation;
+    use super::ObrcError;
+    use super::Statistics;
+    use super::MAGIC;
+    use crate::solutions::solution_streaming::SolutionStreaming;
+    use crate::solutions::solver::OnError;
+    use crate::solutions::solver::SolverOptions;
+
+    fn partial(aggregation: &Aggregation) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        write_partial(aggregation, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let aggregation: Aggregation = SolutionStreaming::solve_reader(
+            read("resources/measurements_20.txt").unwrap().as_slice(),
+        )
+        .unwrap();
+
+        let bytes: Vec<u8> = partial(&aggregation);
+
+        assert_eq!(MAGIC, &bytes[..8]);
+        assert_eq!(aggregation, read_partial(bytes.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip_histograms() {
+        let options: SolverOptions = SolverOptions {
+            histograms: true,
+            ..SolverOptions::default()
+        };
+        let aggregation: Aggregation = SolutionStreaming::solve_reader_with_options(
+            read("resources/measurements_20.txt").unwrap().as_slice(),
+            &options,
+        )
+        .unwrap();
+
+        let actual: Aggregation = read_partial(partial(&aggregation).as_slice()).unwrap();
+
+        assert_eq!(aggregation, actual);
+        assert_eq!(Some(-0.1), actual.stations["Hamburg"].percentile(50.0));
+    }
+
+    #[test]
+    fn test_round_trip_rejected() {
+        let options: SolverOptions = SolverOptions {
+            on_error: OnError::Skip,
+            ..SolverOptions::default()
+        };
+        let aggregation: Aggregation = SolutionStreaming::solve_reader_with_options(
+            read("resources/measurements_corrupted.txt")
+                .unwrap()
+                .as_slice(),
+            &options,
+        )
+        .unwrap();
+        assert!(aggregation.rejected > 0);
+
+        assert_eq!(
+            aggregation,
+            read_partial(partial(&aggregation).as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip_empty() {
+        let bytes: Vec<u8> = partial(&Aggregation::default());
+
+        assert_eq!(8 + 2 + 8 + 4 + 8, bytes.len());
+        assert_eq!(
+            Aggregation::default(),
+            read_partial(bytes.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_partials() {
+        let input: Vec<u8> = read("resources/measurements_20.txt").unwrap();
+        let split: usize = input[..input.len() / 2]
+            .iter()
+            .rposition(|byte: &u8| *byte == b'\n')
+            .unwrap()
+            + 1;
+        let (first, second) = (&input[..split], &input[split..]);
+        assert!(!first.is_empty() && !second.is_empty());
+
+        let mut merged: Aggregation = Aggregation::default();
+        for shard in [first, second] {
+            let bytes: Vec<u8> = partial(&SolutionStreaming::solve_reader(shard).unwrap());
+            merged.merge(read_partial(bytes.as_slice()).unwrap());
+        }
+
+        assert_eq!(
+            SolutionStreaming::solve_reader(input.as_slice()).unwrap(),
+            merged
+        );
+    }
+
+    #[test]
+    fn test_invalid_partials() {
+        let aggregation: Aggregation =
+            SolutionStreaming::solve_reader(b"Hamburg;12.0\nBulawayo;8.9\n".as_slice()).unwrap();
+        let bytes: Vec<u8> = partial(&aggregation);
+
+        let mut corrupted: Vec<u8> = bytes.clone();
+        corrupted[20] ^= 1;
+        let mut bad_magic: Vec<u8> = bytes.clone();
+        bad_magic[0] = b'X';
+        let mut bad_version: Vec<u8> = bytes[..bytes.len() - 8].to_vec();
+        bad_version[8] = 2;
+        bad_version.extend_from_slice(&checksum(&bad_version).to_le_bytes());
+        let mut truncated: Vec<u8> = bytes[..bytes.len() - 9].to_vec();
+        truncated.extend_from_slice(&checksum(&truncated).to_le_bytes());
+
+        for (invalid, message) in [
+            (corrupted, "checksum mismatch"),
+            (bad_magic, "missing magic header"),
+            (bad_version, "unsupported version 2"),
+            (truncated, "truncated stations"),
+            (bytes[..4].to_vec(), "missing magic header"),
+            (bytes[..12].to_vec(), "truncated header"),
+        ] {
+            assert!(
+                matches!(read_partial(invalid.as_slice()), Err(ObrcError::InvalidPartial(actual)) if actual == message),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_inconsistent_partials() {
+        let stats = |temperatures: &[i64]| -> Statistics {
+            let mut stats: Statistics = Statistics::with_histogram();
+            temperatures
+                .iter()
+                .for_each(|temperature: &i64| stats.update(*temperature));
+            stats
+        };
+        let resigned = |mut bytes: Vec<u8>| -> Vec<u8> {
+            bytes.truncate(bytes.len() - 8);
+            bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+            bytes
+        };
+
+        let mut inverted: Statistics = stats(&[120, -34]);
+        (inverted.curr_min, inverted.curr_max) = (inverted.curr_max, inverted.curr_min);
+        let mut miscounted: Statistics = stats(&[120, -34]);
+        miscounted.count = 3;
+        let mut duplicate: Vec<u8> = partial(
+            &[("Aa", stats(&[10])), ("Ab", stats(&[20]))]
+                .into_iter()
+                .collect(),
+        );
+        let second_name: usize = duplicate
+            .windows(2)
+            .rposition(|name: &[u8]| name == b"Ab")
+            .unwrap();
+        duplicate[second_name + 1] = b'a';
+
+        for (invalid, message) in [
+            (
+                partial(&[("Hamburg", inverted)].into_iter().collect()),
+                "station \"Hamburg\" has no measurements or a minimum above its maximum",
+            ),
+            (
+                partial(&[("Hamburg", miscounted)].into_iter().collect()),
+                "histogram of station \"Hamburg\" counts 2 measurements instead of 3",
+            ),
+            (resigned(duplicate), "duplicate station \"Aa\""),
+        ] {
+            assert!(
+                matches!(read_partial(invalid.as_slice()), Err(ObrcError::InvalidPartial(actual)) if actual == message),
+                "{}",
+                message
+            );
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+
+use super::error::ObrcError;
+use super::solver::OnError;
+
+/// Where a slice of the input starts: its offset in bytes and the number of lines before it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Origin {
+    pub(crate) offset: u64,
+    pub(crate) lines: u64,
+}
+
+/// Malformed lines found by a solver, handled according to an [`OnError`] policy.
+/// Under [`OnError::Quarantine`], the lines found by [`Rejected::new`] are written to the quarantine file
+/// as soon as they are found, while the lines of a [`Rejected::default`] are kept in memory,
+/// e.g. for a chunk of the input, until [`Rejected::merge`] writes them out in input order.
+#[derive(Debug, Default)]
+pub(crate) struct Rejected {
+    count: u64,
+    /// The lines themselves, each followed by `\n`, kept for [`OnError::Quarantine`] only
+    /// and without a quarantine file.
+    lines: Vec<u8>,
+    /// The quarantine file the lines are written to.
+    file: Option<BufWriter<File>>,
+}
+
+impl Rejected {
+    /// Malformed lines of the whole input: under [`OnError::Quarantine`],
+    /// the quarantine file is created and the lines are written to it as they are found.
+    pub(crate) fn new(on_error: &OnError) -> Result<Rejected, ObrcError> {
+        let file: Option<BufWriter<File>> = match on_error {
+            OnError::Quarantine(path) => Some(BufWriter::new(File::create(path)?)),
+            OnError::Fail | OnError::Skip => None,
+        };
+        Ok(Rejected {
+            file,
+            ..Rejected::default()
+        })
+    }
+
+    /// Handle a malformed line within `input`, a slice of the input that starts at `origin`:
+    /// return its position as an error under [`OnError::Fail`], and count it otherwise.
+    #[cold]
+    pub(crate) fn reject(
+        &mut self,
+        input: &[u8],
+        origin: Origin,
+        line: &[u8],
+        on_error: &OnError,
+    ) -> Result<(), ObrcError> {
+        match on_error {
+            OnError::Fail => {
+                let start: usize = line.as_ptr() as usize - input.as_ptr() as usize;
+                let lines_before: usize = input[..start]
+                    .iter()
+                    .filter(|byte: &&u8| **byte == b'\n')
+                    .count();
+                Err(ObrcError::MalformedLine {
+                    line: String::from_utf8_lossy(line).into_owned(),
+                    offset: origin.offset + start as u64,
+                    line_number: origin.lines + lines_before as u64 + 1,
+                })
+            }
+            OnError::Skip => {
+                self.count += 1;
+                Ok(())
+            }
+            OnError::Quarantine(_) => {
+                self.count += 1;
+                self.write(line)?;
+                self.write(b"\n")
+            }
+        }
+    }
+
+    /// Like [`Rejected::reject`], for the start of a line too long to be held in memory at once:
+    /// the rest of the line follows with [`Rejected::reject_rest`], up to and including its newline.
+    #[cold]
+    pub(crate) fn reject_start(
+        &mut self,
+        input: &[u8],
+        origin: Origin,
+        start: &[u8],
+        on_error: &OnError,
+    ) -> Result<(), ObrcError> {
+        match on_error {
+            OnError::Quarantine(_) => {
+                self.count += 1;
+                self.write(start)
+            }
+            OnError::Fail | OnError::Skip => self.reject(input, origin, start, on_error),
+        }
+    }
+
+    /// Quarantine more of the line started with [`Rejected::reject_start`].
+    #[cold]
+    pub(crate) fn reject_rest(&mut self, rest: &[u8], on_error: &OnError) -> Result<(), ObrcError> {
+        match on_error {
+            OnError::Quarantine(_) => self.write(rest),
+            OnError::Fail | OnError::Skip => Ok(()),
+        }
+    }
+
+    /// Combine the malformed lines found further in the input,
+    /// writing them to the quarantine file if this has one.
+    pub(crate) fn merge(&mut self, other: Rejected) -> Result<(), ObrcError> {
+        self.count += other.count;
+        self.write(&other.lines)
+    }
+
+    /// Flush the quarantine file, if any, and return the number of malformed lines.
+    pub(crate) fn finish(self) -> Result<u64, ObrcError> {
+        if let Some(mut file) = self.file {
+            file.flush()?;
+        }
+        Ok(self.count)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), ObrcError> {
+        match &mut self.file {
+            Some(file) => file.write_all(bytes)?,
+            None => self.lines.extend_from_slice(bytes),
+        }
+        Ok(())
+    }
+}
+
+/// The line within `input` that spans from the start of `first` to the end of `last`,
+/// e.g. from the station name to the temperature of a record.
+pub(crate) fn span<'a>(input: &'a [u8], first: &[u8], last: &[u8]) -> &'a [u8] {
+    let start: usize = first.as_ptr() as usize - input.as_ptr() as usize;
+    let end: usize = last.as_ptr() as usize + last.len() - input.as_ptr() as usize;
+    &input[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::metadata;
+    use std::fs::read;
+    use std::fs::remove_file;
+
+    use super::Origin;
+    use super::Rejected;
+
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
+    use crate::solutions::aggregation::Aggregation;
+    use crate::solutions::error::ObrcError;
+    use crate::solutions::solution_basic::SolutioBasic;
+    use crate::solutions::solution_mmap::SolutionMmap;
+    use crate::solutions::solution_naive::SolutioNaive;
+    use crate::solutions::solution_open_addressing::SolutionOpenAddressing;
+    use crate::solutions::solution_rayon_fxhash::SolutionRayonFxHash;
+    use crate::solutions::solution_streaming::SolutionStreaming;
+    use crate::solutions::solver::OnError;
+    use crate::solutions::solver::Solver;
+    use crate::solutions::solver::SolverOptions;
+
+    const INPUT_PATH: &str = "resources/measurements_corrupted.txt";
+
+    type SolveFn = fn(&str, &SolverOptions) -> Result<Aggregation, ObrcError>;
+
+    const SOLVERS: [(&str, SolveFn); 6] = [
+        ("basic", SolutioBasic::solve_obrc_with_options),
+        ("naive", SolutioNaive::solve_obrc_with_options),
+        ("rayon", SolutionRayonFxHash::solve_obrc_with_options),
+        ("mmap", SolutionMmap::solve_obrc_with_options),
+        (
+            "open_addressing",
+            SolutionOpenAddressing::solve_obrc_with_options,
+        ),
+        ("streaming", SolutionStreaming::solve_obrc_with_options),
+    ];
+
+    #[test]
+    fn test_fail() {
+        for (name, solve) in SOLVERS {
+            assert!(
+                matches!(
+                    solve(INPUT_PATH, &SolverOptions::default()),
+                    Err(ObrcError::MalformedLine { line, offset: 13, line_number: 2 })
+                        if line == "Bulawayo;8.9\r"
+                ),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_skip() {
+        let options: SolverOptions = SolverOptions {
+            on_error: OnError::Skip,
+            ..SolverOptions::default()
+        };
+
+        for (name, solve) in SOLVERS {
+            let actual: Aggregation = solve(INPUT_PATH, &options).unwrap();
+
+            assert_eq!(5, actual.rejected, "{}", name);
+            assert_eq!(
+                "{Bulawayo=-4.5/-4.5/-4.5, Hamburg=12.0/23.1/34.2, St. John's=15.2/15.2/15.2}\n",
+                format(&actual, Format::Official),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_quarantine() {
+        for (name, solve) in SOLVERS {
+            let path: String = temp_dir()
+                .join(format!(
+                    "obrc_quarantine_{}_{}.txt",
+                    name,
+                    std::process::id()
+                ))
+                .to_string_lossy()
+                .into_owned();
+            let options: SolverOptions = SolverOptions {
+                on_error: OnError::Quarantine(path.clone()),
+                ..SolverOptions::default()
+            };
+
+            let actual: Aggregation = solve(INPUT_PATH, &options).unwrap();
+            let quarantined: Vec<u8> = read(&path).unwrap();
+            remove_file(&path).unwrap();
+
+            assert_eq!(5, actual.rejected, "{}", name);
+            assert_eq!(
+                "Bulawayo;8.9\r\nHamburg12.0\nPalembang;38.8 \nCracow;abc\nZ\u{fc}r\u{fffd}ich;1.0\n",
+                String::from_utf8_lossy(&quarantined),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_quarantine_written_as_found() {
+        let path: String = temp_dir()
+            .join(format!("obrc_quarantine_stream_{}.txt", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let on_error: OnError = OnError::Quarantine(path.clone());
+        let input: Vec<u8> = b"Hamburg12.0\n".repeat(10_000);
+
+        let mut rejected: Rejected = Rejected::new(&on_error).unwrap();
+        for line in input.split_inclusive(|byte: &u8| *byte == b'\n') {
+            rejected
+                .reject(
+                    &input,
+                    Origin::default(),
+                    &line[..line.len() - 1],
+                    &on_error,
+                )
+                .unwrap();
+        }
+        let written: u64 = metadata(&path).unwrap().len();
+        let count: u64 = rejected.finish().unwrap();
+        let quarantined: Vec<u8> = read(&path).unwrap();
+        remove_file(&path).unwrap();
+
+        assert!(written > 0);
+        assert_eq!(10_000, count);
+        assert_eq!(input, quarantined);
+    }
+}
@@ -1,103 +1,123 @@
 use std::collections::HashMap;
-use std::fmt::Display;
-use std::fmt::Formatter;
-use std::fmt::Result;
-use std::fmt::Write;
-use std::fs::read_to_string;
-
-use crate::utils::rounding::round_one_digit_precision;
+use std::fs::read;
 
+use super::aggregation::Aggregation;
+use super::aggregation::Statistics;
+use super::error::ObrcError;
+use super::rejected::Origin;
+use super::rejected::Rejected;
 use super::solver::Solver;
+use super::solver::SolverOptions;
+use crate::utils::parsing::parse_temperature;
 
 #[derive(Default)]
 pub struct SolutioBasic {}
 
 impl Solver for SolutioBasic {
-    fn solve_obrc(input_path: &str) -> String {
-        let input: String = read_to_string(input_path).unwrap();
-        let mut output: String = String::new();
+    fn solve_obrc_with_options(
+        input_path: &str,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        let input: Vec<u8> = read(input_path)?;
         let mut map: HashMap<&str, Statistics> = HashMap::<&str, Statistics>::default();
+        let mut rejected: Rejected = Rejected::new(&options.on_error)?;
 
-        for line in input.lines() {
-            if let Some((name, temperature)) = parse_line(line) {
-                map.entry(name)
-                    .and_modify(|stats: &mut Statistics| {
-                        stats.min = stats.min.min(temperature);
-                        stats.max = stats.max.max(temperature);
-                        stats.count += 1;
-                        stats.mean = (stats.mean * (stats.count - 1) as f64 + temperature)
-                            / stats.count as f64;
-                    })
-                    .or_insert(Statistics {
-                        min: temperature,
-                        max: temperature,
-                        mean: temperature,
-                        count: 1,
-                    });
+        for line in input.split(|byte: &u8| *byte == b'\n') {
+            if line.is_empty() {
+                continue;
+            }
+            match parse_line(line) {
+                Some((name, temperature)) => map
+                    .entry(name)
+                    .or_insert_with(|| options.statistics())
+                    .update(temperature as i64),
+                None => rejected.reject(&input, Origin::default(), line, &options.on_error)?,
             }
         }
 
-        for (name, stats) in map {
-            writeln!(output, "{}={}", name, stats).unwrap();
-        }
-
-        output
+        let mut aggregation: Aggregation = map.into_iter().collect();
+        aggregation.rejected = rejected.finish()?;
+        Ok(aggregation)
     }
 }
 
-fn parse_line(line: &str) -> Option<(&str, f64)> {
-    let (name, temperature_as_str) = line.split_once(';')?;
-    let temperature: f64 = temperature_as_str.parse::<f64>().ok()?;
+fn parse_line(line: &[u8]) -> Option<(&str, i16)> {
+    let (name, temperature_as_str) = std::str::from_utf8(line).ok()?.split_once(';')?;
+    let temperature: i16 = parse_temperature(temperature_as_str.as_bytes())?;
     Some((name, temperature))
 }
 
-#[derive(Default)]
-struct Statistics {
-    min: f64,
-    max: f64,
-    mean: f64,
-    count: u64,
-}
-
-impl Display for Statistics {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(
-            f,
-            "{}/{}/{}",
-            round_one_digit_precision(self.min),
-            round_one_digit_precision(self.max),
-            round_one_digit_precision(self.mean)
-        )
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
     use std::fs::read_to_string;
 
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
+
+    use std::env::temp_dir;
+    use std::fs::remove_file;
+    use std::fs::write;
+    use std::path::PathBuf;
+
+    use super::parse_line;
+    use super::Aggregation;
     use super::SolutioBasic;
     use super::Solver;
+    use super::SolverOptions;
+    use crate::solutions::solution_naive::SolutioNaive;
+    use crate::solutions::solver::OnError;
+
+    const INVALID_TEMPERATURES: [&str; 7] = ["1e1", "150.0", "1.23", "inf", "NaN", "+1.0", " 1.0"];
+
+    #[test]
+    fn test_parse_line_invalid_temperatures() {
+        assert_eq!(Some(("A", -123)), parse_line(b"A;-12.3"));
+        for temperature in INVALID_TEMPERATURES {
+            assert_eq!(
+                None,
+                parse_line(format!("A;{}", temperature).as_bytes()),
+                "{}",
+                temperature
+            );
+        }
+    }
+
+    #[test]
+    fn test_solution_invalid_temperatures() {
+        let path: PathBuf = temp_dir().join(format!("obrc_basic_{}.txt", std::process::id()));
+        let input: String = INVALID_TEMPERATURES
+            .iter()
+            .map(|temperature: &&str| format!("A;{}\n", temperature))
+            .collect::<String>()
+            + "A;1.0\n";
+        write(&path, input).unwrap();
+        let options: SolverOptions = SolverOptions {
+            on_error: OnError::Skip,
+            ..SolverOptions::default()
+        };
+
+        let actual: Aggregation =
+            SolutioBasic::solve_obrc_with_options(path.to_str().unwrap(), &options).unwrap();
+        let expected: Aggregation =
+            SolutioNaive::solve_obrc_with_options(path.to_str().unwrap(), &options).unwrap();
+        remove_file(&path).unwrap();
+
+        assert_eq!(INVALID_TEMPERATURES.len() as u64, actual.rejected);
+        assert_eq!(expected, actual);
+    }
 
     #[test]
     fn test_solution_1000000() {
-        let expected: HashSet<String> = read_to_string("resources/results_1000000.txt")
-            .unwrap()
+        let expected_output: String = read_to_string("resources/results_1000000.txt").unwrap();
+        let mut expected: Vec<&str> = expected_output
             .lines()
             .filter(|line: &&str| !line.is_empty())
-            .map(String::from)
             .collect();
+        expected.sort_by_key(|line: &&str| line.split_once('=').unwrap().0);
 
-        let actual: HashSet<String> =
-            SolutioBasic::solve_obrc("resources/measurements_1000000.txt")
-                .lines()
-                .filter(|line: &&str| !line.is_empty())
-                .map(String::from)
-                .collect();
+        let actual: Aggregation =
+            SolutioBasic::solve_obrc("resources/measurements_1000000.txt").unwrap();
 
-        assert_eq!(expected.len(), actual.len());
-        expected
-            .iter()
-            .for_each(|expected_line: &String| assert!(actual.contains(expected_line)));
+        assert_eq!(expected.join("\n") + "\n", format(&actual, Format::Text));
     }
 }
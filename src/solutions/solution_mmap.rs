@@ -0,0 +1,143 @@
+use super::aggregation::Aggregation;
+use super::aggregation::Statistics;
+use super::chunks::map_input;
+use super::chunks::solve_chunks;
+use super::chunks::station_name;
+use super::error::ObrcError;
+use super::rejected::span;
+use super::rejected::Origin;
+use super::rejected::Rejected;
+use super::solver::Solver;
+use super::solver::SolverOptions;
+use crate::utils::parsing::parse_temperature;
+use crate::utils::scanner::Records;
+
+use memmap2::Mmap;
+use rustc_hash::FxHashMap;
+
+/// Memory-maps the input instead of reading it into a `String`,
+/// and aggregates newline-aligned chunks of it on one thread each, scanning them with [`Records`].
+#[derive(Default)]
+pub struct SolutionMmap {}
+
+impl Solver for SolutionMmap {
+    fn solve_obrc_with_options(
+        input_path: &str,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        let mmap: Mmap = map_input(input_path)?;
+        let threads: usize = options.thread_count();
+
+        solve(&mmap, threads, options)
+    }
+}
+
+pub(crate) fn solve(
+    input: &[u8],
+    threads: usize,
+    options: &SolverOptions,
+) -> Result<Aggregation, ObrcError> {
+    let maps: Vec<Result<Partial<'_>, ObrcError>> =
+        solve_chunks(input, threads, |chunk: &[u8]| {
+            solve_chunk(input, chunk, options)
+        });
+
+    let mut output_map: FxHashMap<&[u8], Statistics> = FxHashMap::<&[u8], Statistics>::default();
+    let mut rejected: Rejected = Rejected::new(&options.on_error)?;
+    for map in maps {
+        let (map, other) = map?;
+        for (name, stats) in map {
+            let acc_stats: &mut Statistics = output_map.entry(name).or_default();
+            acc_stats.merge(&stats);
+        }
+        rejected.merge(other)?;
+    }
+
+    let mut aggregation: Aggregation = output_map
+        .into_iter()
+        .map(|(name, stats)| (station_name(name), stats))
+        .collect();
+    aggregation.rejected = rejected.finish()?;
+    Ok(aggregation)
+}
+
+/// Statistics of every weather station and the malformed lines, in a chunk of the input.
+type Partial<'a> = (FxHashMap<&'a [u8], Statistics>, Rejected);
+
+fn solve_chunk<'a>(
+    input: &[u8],
+    chunk: &'a [u8],
+    options: &SolverOptions,
+) -> Result<Partial<'a>, ObrcError> {
+    let mut map: FxHashMap<&[u8], Statistics> = FxHashMap::<&[u8], Statistics>::default();
+    let mut rejected: Rejected = Rejected::default();
+
+    for record in Records::new(chunk) {
+        let (name, raw_temperature) = match record {
+            Ok(record) => record,
+            Err(line) => {
+                rejected.reject(input, Origin::default(), line, &options.on_error)?;
+                continue;
+            }
+        };
+        match parse_temperature(raw_temperature) {
+            Some(temperature) => match map.get_mut(name) {
+                Some(stats) => stats.update(temperature as i64),
+                None if std::str::from_utf8(name).is_ok() => map
+                    .entry(name)
+                    .or_insert_with(|| options.statistics())
+                    .update(temperature as i64),
+                None => rejected.reject(
+                    input,
+                    Origin::default(),
+                    span(chunk, name, raw_temperature),
+                    &options.on_error,
+                )?,
+            },
+            None => rejected.reject(
+                input,
+                Origin::default(),
+                span(chunk, name, raw_temperature),
+                &options.on_error,
+            )?,
+        }
+    }
+
+    Ok((map, rejected))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
+
+    use super::Aggregation;
+    use super::ObrcError;
+    use super::SolutionMmap;
+    use super::Solver;
+
+    #[test]
+    fn test_solution_1000000() {
+        let expected_output: String = read_to_string("resources/results_1000000.txt").unwrap();
+        let mut expected: Vec<&str> = expected_output
+            .lines()
+            .filter(|line: &&str| !line.is_empty())
+            .collect();
+        expected.sort_by_key(|line: &&str| line.split_once('=').unwrap().0);
+
+        let actual: Aggregation =
+            SolutionMmap::solve_obrc("resources/measurements_1000000.txt").unwrap();
+
+        assert_eq!(expected.join("\n") + "\n", format(&actual, Format::Text));
+    }
+
+    #[test]
+    fn test_solution_malformed() {
+        assert!(matches!(
+            SolutionMmap::solve_obrc("resources/measurements_malformed.txt"),
+            Err(ObrcError::MalformedLine { line, offset: 26, line_number: 3 }) if line == "Hamburg12.0"
+        ));
+    }
+}
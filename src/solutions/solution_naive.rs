@@ -1,126 +1,86 @@
 use std::collections::HashMap;
-use std::fmt::Display;
-use std::fmt::Formatter;
-use std::fmt::Result;
-use std::fmt::Write;
-use std::fs::read_to_string;
+use std::fs::read;
 
+use super::aggregation::Aggregation;
+use super::aggregation::Statistics;
+use super::error::ObrcError;
+use super::rejected::Origin;
+use super::rejected::Rejected;
 use super::solver::Solver;
-use crate::utils::parsing::to;
-use crate::utils::rounding::round_one_digit_precision;
+use super::solver::SolverOptions;
+use crate::utils::parsing::parse_line;
 
 #[derive(Default)]
 pub struct SolutioNaive {}
 
 impl Solver for SolutioNaive {
-    fn solve_obrc(input_path: &str) -> String {
-        let input: String = read_to_string(input_path).unwrap();
-        let mut output: String = String::new();
+    fn solve_obrc_with_options(
+        input_path: &str,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        let input: Vec<u8> = read(input_path)?;
+        let mut rejected: Rejected = Rejected::new(&options.on_error)?;
 
-        input
-            .lines()
-            .map(|line: &str| -> (&str, i64) {
-                line.split_once(';')
-                    .map(|(name, temperature_as_str)| {
-                        (name, (to::<f64>(temperature_as_str) * 10.0) as i64)
-                    })
-                    .unwrap()
-            })
-            .fold(
+        let map: HashMap<&str, Statistics> = input
+            .split(|byte: &u8| *byte == b'\n')
+            .filter(|line: &&[u8]| !line.is_empty())
+            .try_fold(
                 HashMap::<&str, Statistics>::default(),
                 |mut acc: HashMap<&str, Statistics>,
-                 (name, temperature): (&str, i64)|
-                 -> HashMap<&str, Statistics> {
-                    let stats: &mut Statistics = acc.entry(name).or_default();
-                    stats.update(temperature);
-                    acc
+                 line: &[u8]|
+                 -> Result<HashMap<&str, Statistics>, ObrcError> {
+                    match parse_line(line) {
+                        Some((name, temperature)) => {
+                            let stats: &mut Statistics =
+                                acc.entry(name).or_insert_with(|| options.statistics());
+                            stats.update(temperature as i64);
+                        }
+                        None => {
+                            rejected.reject(&input, Origin::default(), line, &options.on_error)?
+                        }
+                    }
+                    Ok(acc)
                 },
-            )
-            .iter_mut()
-            .map(|(name, stats)| {
-                stats.compute();
-                (name, stats)
-            })
-            .for_each(|(name, stats)| {
-                writeln!(output, "{}={}", name, stats).unwrap();
-            });
-
-        output
-    }
-}
-
-struct Statistics {
-    curr_min: i64,
-    curr_max: i64,
-    acc_sum: i64,
-    count: u64,
-    min: f64,
-    max: f64,
-    mean: f64,
-}
-
-impl Statistics {
-    fn update(&mut self, temperature: i64) {
-        self.curr_min = self.curr_min.min(temperature);
-        self.curr_max = self.curr_max.max(temperature);
-        self.acc_sum += temperature;
-        self.count += 1;
-    }
-
-    fn compute(&mut self) {
-        self.min = round_one_digit_precision(self.curr_min as f64 / 10.0);
-        self.max = round_one_digit_precision(self.curr_max as f64 / 10.0);
-        self.mean = round_one_digit_precision(self.acc_sum as f64 / (10.0 * self.count as f64));
-    }
-}
-
-impl Default for Statistics {
-    fn default() -> Self {
-        Self {
-            curr_min: i64::MAX,
-            curr_max: i64::MIN,
-            acc_sum: 0,
-            count: 0,
-            max: 0.0,
-            min: 0.0,
-            mean: 0.0,
-        }
-    }
-}
+            )?;
 
-impl Display for Statistics {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}/{}/{}", self.min, self.max, self.mean)
+        let mut aggregation: Aggregation = map.into_iter().collect();
+        aggregation.rejected = rejected.finish()?;
+        Ok(aggregation)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
     use std::fs::read_to_string;
 
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
+
+    use super::Aggregation;
+    use super::ObrcError;
     use super::SolutioNaive;
     use super::Solver;
 
     #[test]
     fn test_solution_1000000() {
-        let expected: HashSet<String> = read_to_string("resources/results_1000000.txt")
-            .unwrap()
+        let expected_output: String = read_to_string("resources/results_1000000.txt").unwrap();
+        let mut expected: Vec<&str> = expected_output
             .lines()
             .filter(|line: &&str| !line.is_empty())
-            .map(String::from)
             .collect();
+        expected.sort_by_key(|line: &&str| line.split_once('=').unwrap().0);
+
+        let actual: Aggregation =
+            SolutioNaive::solve_obrc("resources/measurements_1000000.txt").unwrap();
 
-        let actual: HashSet<String> =
-            SolutioNaive::solve_obrc("resources/measurements_1000000.txt")
-                .lines()
-                .filter(|line: &&str| !line.is_empty())
-                .map(String::from)
-                .collect();
+        assert_eq!(expected.join("\n") + "\n", format(&actual, Format::Text));
+    }
 
-        assert_eq!(expected.len(), actual.len());
-        expected
-            .iter()
-            .for_each(|expected_line: &String| assert!(actual.contains(expected_line)));
+    #[test]
+    fn test_solution_malformed() {
+        assert!(matches!(
+            SolutioNaive::solve_obrc("resources/measurements_malformed.txt"),
+            Err(ObrcError::MalformedLine { line, offset: 26, line_number: 3 }) if line == "Hamburg12.0"
+        ));
     }
 }
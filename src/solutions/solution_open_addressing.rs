@@ -0,0 +1,157 @@
+use super::aggregation::Aggregation;
+use super::chunks::map_input;
+use super::chunks::solve_chunks;
+use super::chunks::station_name;
+use super::error::ObrcError;
+use super::rejected::Origin;
+use super::rejected::Rejected;
+use super::solver::Solver;
+use super::solver::SolverOptions;
+use crate::utils::parsing::parse_temperature;
+use crate::utils::station_table::hash_name;
+use crate::utils::station_table::StationTable;
+use crate::utils::station_table::MAX_STATIONS;
+
+use memmap2::Mmap;
+
+/// Like [`super::solution_mmap::SolutionMmap`], but aggregates into a [`StationTable`]
+/// keyed by the raw name bytes, hashed while scanning for the `;`.
+#[derive(Default)]
+pub struct SolutionOpenAddressing {}
+
+impl Solver for SolutionOpenAddressing {
+    fn solve_obrc_with_options(
+        input_path: &str,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        let mmap: Mmap = map_input(input_path)?;
+        let threads: usize = options.thread_count();
+
+        solve(&mmap, threads, options)
+    }
+}
+
+pub(crate) fn solve(
+    input: &[u8],
+    threads: usize,
+    options: &SolverOptions,
+) -> Result<Aggregation, ObrcError> {
+    let tables: Vec<Result<(StationTable, Rejected), ObrcError>> =
+        solve_chunks(input, threads, |chunk: &[u8]| {
+            solve_chunk(input, chunk, options)
+        });
+
+    let mut output_table: StationTable = StationTable::with_statistics(options.statistics());
+    let mut rejected: Rejected = Rejected::new(&options.on_error)?;
+    for table in tables {
+        let (table, other) = table?;
+        output_table
+            .merge(&table)
+            .ok_or(ObrcError::TooManyStations(MAX_STATIONS))?;
+        rejected.merge(other)?;
+    }
+
+    let mut aggregation: Aggregation = output_table
+        .iter()
+        .map(|(name, stats)| (station_name(name), stats.clone()))
+        .collect();
+    aggregation.rejected = rejected.finish()?;
+    Ok(aggregation)
+}
+
+fn solve_chunk(
+    input: &[u8],
+    chunk: &[u8],
+    options: &SolverOptions,
+) -> Result<(StationTable, Rejected), ObrcError> {
+    let mut table: StationTable = StationTable::with_statistics(options.statistics());
+    let mut rejected: Rejected = Rejected::default();
+    let mut rest: &[u8] = chunk;
+
+    while !rest.is_empty() {
+        if rest[0] == b'\n' {
+            rest = &rest[1..];
+            continue;
+        }
+        let parsed: Option<(usize, u64)> = hash_name(rest);
+        let line_len: usize = rest[parsed.map_or(0, |(name_len, _)| name_len + 1)..]
+            .iter()
+            .position(|byte: &u8| *byte == b'\n')
+            .map_or(rest.len(), |position: usize| {
+                parsed.map_or(0, |(name_len, _)| name_len + 1) + position
+            });
+        let line: &[u8] = &rest[..line_len];
+        rest = &rest[(line_len + 1).min(rest.len())..];
+
+        let Some((name_len, hash)) = parsed else {
+            rejected.reject(input, Origin::default(), line, &options.on_error)?;
+            continue;
+        };
+        let name: &[u8] = &line[..name_len];
+        let Some(temperature) = parse_temperature(&line[name_len + 1..]) else {
+            rejected.reject(input, Origin::default(), line, &options.on_error)?;
+            continue;
+        };
+        match table.get_mut(name, hash) {
+            Some(stats) => stats.update(temperature as i64),
+            None if std::str::from_utf8(name).is_ok() => table
+                .entry(name, hash)
+                .ok_or(ObrcError::TooManyStations(MAX_STATIONS))?
+                .update(temperature as i64),
+            None => rejected.reject(input, Origin::default(), line, &options.on_error)?,
+        }
+    }
+
+    Ok((table, rejected))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
+
+    use super::solve;
+    use super::Aggregation;
+    use super::ObrcError;
+    use super::SolutionOpenAddressing;
+    use super::Solver;
+    use super::SolverOptions;
+    use crate::utils::station_table::MAX_STATIONS;
+
+    #[test]
+    fn test_solution_1000000() {
+        let expected_output: String = read_to_string("resources/results_1000000.txt").unwrap();
+        let mut expected: Vec<&str> = expected_output
+            .lines()
+            .filter(|line: &&str| !line.is_empty())
+            .collect();
+        expected.sort_by_key(|line: &&str| line.split_once('=').unwrap().0);
+
+        let actual: Aggregation =
+            SolutionOpenAddressing::solve_obrc("resources/measurements_1000000.txt").unwrap();
+
+        assert_eq!(expected.join("\n") + "\n", format(&actual, Format::Text));
+    }
+
+    #[test]
+    fn test_solution_malformed() {
+        assert!(matches!(
+            SolutionOpenAddressing::solve_obrc("resources/measurements_malformed.txt"),
+            Err(ObrcError::MalformedLine { line, offset: 26, line_number: 3 }) if line == "Hamburg12.0"
+        ));
+    }
+
+    #[test]
+    fn test_solution_too_many_stations() {
+        let input: String = (0..=MAX_STATIONS)
+            .map(|index: usize| format!("Station {};1.0\n", index))
+            .collect();
+
+        assert!(matches!(
+            solve(input.as_bytes(), 4, &SolverOptions::default()),
+            Err(ObrcError::TooManyStations(MAX_STATIONS))
+        ));
+    }
+}
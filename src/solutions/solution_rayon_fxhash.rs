@@ -1,149 +1,158 @@
-use std::fmt::Display;
-use std::fmt::Formatter;
-use std::fmt::Result;
-use std::fmt::Write;
-use std::fs::read_to_string;
+use std::fs::read;
 
+use super::aggregation::Aggregation;
+use super::aggregation::Statistics;
+use super::error::ObrcError;
+use super::rejected::Origin;
+use super::rejected::Rejected;
 use super::solver::Solver;
-use crate::utils::parsing::to;
-use crate::utils::rounding::round_one_digit_precision;
+use super::solver::SolverOptions;
+use crate::utils::parsing::parse_line;
 
 use rayon::prelude::*;
 use rustc_hash::FxHashMap;
 
+/// Statistics of every weather station and the malformed lines, in a part of the input,
+/// and the first error in that part under [`OnError::Fail`](super::solver::OnError::Fail).
+type Partial<'a> = (FxHashMap<&'a str, Statistics>, Rejected, Option<ObrcError>);
+
 #[derive(Default)]
 pub struct SolutionRayonFxHash {}
 
 impl Solver for SolutionRayonFxHash {
-    fn solve_obrc(input_path: &str) -> String {
-        let input: String = read_to_string(input_path).unwrap();
-        let mut output: String = String::new();
-
-        let mut output_map: FxHashMap<&str, Statistics> = input
-            .par_lines()
-            .map(|line: &str| -> (&str, i64) {
-                line.split_once(';')
-                    .map(|(name, temperature_as_str)| {
-                        (name, (to::<f64>(temperature_as_str) * 10.0) as i64)
-                    })
-                    .unwrap()
-            })
+    fn solve_obrc_with_options(
+        input_path: &str,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        let input: Vec<u8> = read(input_path)?;
+
+        // Parts are not short-circuited on errors, so that the error returned is the first one
+        // of the input, whichever part a worker reaches first.
+        let (output_map, rejected, error): Partial<'_> = input
+            .par_split(|byte: &u8| *byte == b'\n')
+            .filter(|line: &&[u8]| !line.is_empty())
             .fold(
-                FxHashMap::<&str, Statistics>::default,
-                |mut acc: FxHashMap<&str, Statistics>,
-                 (name, temperature): (&str, i64)|
-                 -> FxHashMap<&str, Statistics> {
-                    let stats: &mut Statistics = acc.entry(name).or_default();
-                    stats.update(temperature);
-                    acc
+                Partial::default,
+                |(mut acc, mut rejected, error): Partial<'_>, line: &[u8]| -> Partial<'_> {
+                    if error.is_some() {
+                        return (acc, rejected, error);
+                    }
+                    match parse_line(line) {
+                        Some((name, temperature)) => {
+                            let stats: &mut Statistics =
+                                acc.entry(name).or_insert_with(|| options.statistics());
+                            stats.update(temperature as i64);
+                            (acc, rejected, None)
+                        }
+                        None => {
+                            let error: Option<ObrcError> = rejected
+                                .reject(&input, Origin::default(), line, &options.on_error)
+                                .err();
+                            (acc, rejected, error)
+                        }
+                    }
                 },
             )
             .reduce(
-                FxHashMap::<&str, Statistics>::default,
-                |mut acc: FxHashMap<&str, Statistics>,
-                 map: FxHashMap<&str, Statistics>|
-                 -> FxHashMap<&str, Statistics> {
+                Partial::default,
+                |(mut acc, mut rejected, error): Partial<'_>,
+                 (map, other, other_error): Partial<'_>|
+                 -> Partial<'_> {
                     for (name, stats) in map {
                         let acc_stats: &mut Statistics = acc.entry(name).or_default();
                         acc_stats.merge(&stats);
                     }
-                    acc
+                    let merge_error: Option<ObrcError> = rejected.merge(other).err();
+                    (
+                        acc,
+                        rejected,
+                        first_error(first_error(error, other_error), merge_error),
+                    )
                 },
             );
+        if let Some(error) = error {
+            return Err(error);
+        }
 
-        output_map
-            .iter_mut()
-            .map(|(name, stats)| {
-                stats.compute();
-                (name, stats)
-            })
-            .for_each(|(name, stats)| {
-                writeln!(output, "{}={}", name, stats).unwrap();
-            });
-
-        output
-    }
-}
-
-pub struct Statistics {
-    curr_min: i64,
-    curr_max: i64,
-    acc_sum: i64,
-    count: u64,
-    min: f64,
-    max: f64,
-    mean: f64,
-}
-
-impl Statistics {
-    fn update(&mut self, temperature: i64) {
-        self.curr_min = self.curr_min.min(temperature);
-        self.curr_max = self.curr_max.max(temperature);
-        self.acc_sum += temperature;
-        self.count += 1;
-    }
-
-    fn merge(&mut self, other: &Statistics) {
-        self.curr_min = self.curr_min.min(other.curr_min);
-        self.curr_max = self.curr_max.max(other.curr_max);
-        self.acc_sum += other.acc_sum;
-        self.count += other.count;
-    }
-
-    fn compute(&mut self) {
-        self.min = round_one_digit_precision(self.curr_min as f64 / 10.0);
-        self.max = round_one_digit_precision(self.curr_max as f64 / 10.0);
-        self.mean = round_one_digit_precision(self.acc_sum as f64 / (10.0 * self.count as f64));
+        let mut aggregation: Aggregation = output_map.into_iter().collect();
+        let mut quarantine: Rejected = Rejected::new(&options.on_error)?;
+        quarantine.merge(rejected)?;
+        aggregation.rejected = quarantine.finish()?;
+        Ok(aggregation)
     }
 }
 
-impl Default for Statistics {
-    fn default() -> Self {
-        Self {
-            curr_min: i64::MAX,
-            curr_max: i64::MIN,
-            acc_sum: 0,
-            count: 0,
-            max: 0.0,
-            min: 0.0,
-            mean: 0.0,
+/// The error of the malformed line with the lowest offset.
+fn first_error(error: Option<ObrcError>, other: Option<ObrcError>) -> Option<ObrcError> {
+    let offset = |error: &ObrcError| -> u64 {
+        match error {
+            ObrcError::MalformedLine { offset, .. } => *offset,
+            _ => u64::MAX,
         }
-    }
-}
-
-impl Display for Statistics {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}/{}/{}", self.min, self.max, self.mean)
+    };
+    match (error, other) {
+        (Some(error), Some(other)) if offset(&other) < offset(&error) => Some(other),
+        (Some(error), _) => Some(error),
+        (None, other) => other,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::env::temp_dir;
     use std::fs::read_to_string;
+    use std::fs::remove_file;
+    use std::fs::write;
+    use std::path::PathBuf;
+
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
 
+    use super::Aggregation;
+    use super::ObrcError;
     use super::SolutionRayonFxHash;
     use super::Solver;
 
     #[test]
     fn test_solution_1000000() {
-        let expected: HashSet<String> = read_to_string("resources/results_1000000.txt")
-            .unwrap()
+        let expected_output: String = read_to_string("resources/results_1000000.txt").unwrap();
+        let mut expected: Vec<&str> = expected_output
             .lines()
             .filter(|line: &&str| !line.is_empty())
-            .map(String::from)
             .collect();
+        expected.sort_by_key(|line: &&str| line.split_once('=').unwrap().0);
 
-        let actual: HashSet<String> =
-            SolutionRayonFxHash::solve_obrc("resources/measurements_1000000.txt")
-                .lines()
-                .filter(|line: &&str| !line.is_empty())
-                .map(String::from)
-                .collect();
-
-        assert_eq!(expected.len(), actual.len());
-        expected
-            .iter()
-            .for_each(|expected_line: &String| assert!(actual.contains(expected_line)));
+        let actual: Aggregation =
+            SolutionRayonFxHash::solve_obrc("resources/measurements_1000000.txt").unwrap();
+
+        assert_eq!(expected.join("\n") + "\n", format(&actual, Format::Text));
+    }
+
+    #[test]
+    fn test_solution_first_malformed_line() {
+        let path: PathBuf = temp_dir().join(format!("obrc_rayon_{}.txt", std::process::id()));
+        let input: String = (0..100_000)
+            .map(|index: usize| match index {
+                0 | 1 => String::from("Hamburg;12.0\n"),
+                _ => format!("Malformed{}\n", index),
+            })
+            .collect();
+        write(&path, input).unwrap();
+
+        for _ in 0..20 {
+            assert!(matches!(
+                SolutionRayonFxHash::solve_obrc(path.to_str().unwrap()),
+                Err(ObrcError::MalformedLine { line, offset: 26, line_number: 3 }) if line == "Malformed2"
+            ));
+        }
+        remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_solution_malformed() {
+        assert!(matches!(
+            SolutionRayonFxHash::solve_obrc("resources/measurements_malformed.txt"),
+            Err(ObrcError::MalformedLine { line, offset: 26, line_number: 3 }) if line == "Hamburg12.0"
+        ));
     }
 }
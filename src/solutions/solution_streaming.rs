@@ -0,0 +1,321 @@
+use std::io::ErrorKind;
+use std::io::Read;
+
+use super::aggregation::Aggregation;
+use super::aggregation::Statistics;
+use super::chunks::station_name;
+use super::error::ObrcError;
+use super::rejected::span;
+use super::rejected::Origin;
+use super::rejected::Rejected;
+use super::solver::Solver;
+use super::solver::SolverOptions;
+use crate::generator::weather_stations::MAX_NAME_LENGTH;
+use crate::utils::compression::open_input;
+use crate::utils::parsing::parse_temperature;
+use crate::utils::scanner::Records;
+
+use rustc_hash::FxHashMap;
+
+/// Size of the buffer the input is read through.
+const BUFFER_SIZE: usize = 1 << 20;
+
+/// Length of the longest valid line, without its newline:
+/// a name of [`MAX_NAME_LENGTH`] bytes, the `;` and a temperature like `-99.9`.
+pub const MAX_LINE_LENGTH: usize = MAX_NAME_LENGTH + ";-99.9".len();
+
+/// Reads the input through a fixed-size buffer, carrying partial lines over to the next refill,
+/// so that it never holds more than the buffer and the statistics in memory.
+/// Lines longer than [`MAX_LINE_LENGTH`] are malformed, and skipped without being held in memory.
+/// Works on any [`Read`], and reads from stdin when the input path is `-`.
+/// Compressed input is decompressed on the fly, see [`open_input`].
+#[derive(Default)]
+pub struct SolutionStreaming {}
+
+impl SolutionStreaming {
+    /// Aggregate the measurements read from the given reader.
+    pub fn solve_reader<R: Read>(reader: R) -> Result<Aggregation, ObrcError> {
+        SolutionStreaming::solve_reader_with_options(reader, &SolverOptions::default())
+    }
+
+    /// Like [`SolutionStreaming::solve_reader`], with the given options.
+    pub fn solve_reader_with_options<R: Read>(
+        reader: R,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        solve(reader, BUFFER_SIZE, options)
+    }
+}
+
+impl Solver for SolutionStreaming {
+    fn solve_obrc_with_options(
+        input_path: &str,
+        options: &SolverOptions,
+    ) -> Result<Aggregation, ObrcError> {
+        SolutionStreaming::solve_reader_with_options(open_input(input_path)?, options)
+    }
+}
+
+fn solve<R: Read>(
+    mut reader: R,
+    buffer_size: usize,
+    options: &SolverOptions,
+) -> Result<Aggregation, ObrcError> {
+    let mut map: FxHashMap<Box<[u8]>, Statistics> = FxHashMap::<Box<[u8]>, Statistics>::default();
+    let mut rejected: Rejected = Rejected::new(&options.on_error)?;
+    let mut origin: Origin = Origin::default();
+    // Room for the longest valid line and its newline, so that a full buffer without a newline
+    // holds the start of a malformed line.
+    let mut buffer: Vec<u8> = vec![0; buffer_size.max(MAX_LINE_LENGTH + 1)];
+    let mut filled: usize = 0;
+    // Whether the rest of a line too long for the buffer is being skipped.
+    let mut skipping: bool = false;
+
+    loop {
+        if filled == buffer.len() {
+            let (start, rest) = buffer[..filled].split_at(MAX_LINE_LENGTH + 1);
+            rejected.reject_start(start, origin, start, &options.on_error)?;
+            rejected.reject_rest(rest, &options.on_error)?;
+            origin.offset += filled as u64;
+            filled = 0;
+            skipping = true;
+        }
+
+        let read: usize = match reader.read(&mut buffer[filled..]) {
+            Ok(read) => read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        if read == 0 {
+            if skipping {
+                rejected.reject_rest(b"\n", &options.on_error)?;
+            } else {
+                solve_lines(&buffer[..filled], origin, &mut map, &mut rejected, options)?;
+            }
+            break;
+        }
+        // The bytes read before were searched for newlines already.
+        let start: usize = filled;
+        filled += read;
+        let Some(last_newline) = buffer[start..filled]
+            .iter()
+            .rposition(|byte: &u8| *byte == b'\n')
+            .map(|position: usize| start + position)
+        else {
+            if skipping {
+                rejected.reject_rest(&buffer[..filled], &options.on_error)?;
+                origin.offset += filled as u64;
+                filled = 0;
+            }
+            continue;
+        };
+
+        let mut lines_start: usize = 0;
+        if skipping {
+            lines_start = buffer[..filled]
+                .iter()
+                .position(|byte: &u8| *byte == b'\n')
+                .unwrap()
+                + 1;
+            rejected.reject_rest(&buffer[..lines_start], &options.on_error)?;
+            origin.offset += lines_start as u64;
+            origin.lines += 1;
+            skipping = false;
+        }
+        let lines: &[u8] = &buffer[lines_start..=last_newline];
+        solve_lines(lines, origin, &mut map, &mut rejected, options)?;
+        origin.offset += lines.len() as u64;
+        origin.lines += lines.iter().filter(|byte: &&u8| **byte == b'\n').count() as u64;
+        buffer.copy_within(last_newline + 1..filled, 0);
+        filled -= last_newline + 1;
+    }
+
+    let mut aggregation: Aggregation = map
+        .into_iter()
+        .map(|(name, stats)| (station_name(&name), stats))
+        .collect();
+    aggregation.rejected = rejected.finish()?;
+    Ok(aggregation)
+}
+
+/// Aggregate whole lines of the input, which start at `origin`.
+fn solve_lines(
+    lines: &[u8],
+    origin: Origin,
+    map: &mut FxHashMap<Box<[u8]>, Statistics>,
+    rejected: &mut Rejected,
+    options: &SolverOptions,
+) -> Result<(), ObrcError> {
+    for record in Records::new(lines) {
+        let (name, raw_temperature) = match record {
+            Ok(record) => record,
+            Err(line) => {
+                rejected.reject(lines, origin, line, &options.on_error)?;
+                continue;
+            }
+        };
+        let line: &[u8] = span(lines, name, raw_temperature);
+        let Some(temperature) = parse_temperature(raw_temperature) else {
+            rejected.reject(lines, origin, line, &options.on_error)?;
+            continue;
+        };
+        match map.get_mut(name) {
+            Some(stats) => stats.update(temperature as i64),
+            None if std::str::from_utf8(name).is_ok() => map
+                .entry(name.into())
+                .or_insert_with(|| options.statistics())
+                .update(temperature as i64),
+            None => rejected.reject(lines, origin, line, &options.on_error)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::read;
+    use std::fs::read_to_string;
+    use std::fs::remove_file;
+    use std::io::Read;
+
+    use crate::output::formatter::format;
+    use crate::output::formatter::Format;
+
+    use super::solve;
+    use super::Aggregation;
+    use super::ObrcError;
+    use super::SolutionStreaming;
+    use super::Solver;
+    use super::SolverOptions;
+    use super::MAX_LINE_LENGTH;
+    use crate::solutions::solution_basic::SolutioBasic;
+    use crate::solutions::solver::OnError;
+
+    /// Reads at most `size` bytes at a time, like a pipe.
+    struct SmallReads<'a> {
+        input: &'a [u8],
+        size: usize,
+    }
+
+    impl Read for SmallReads<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len: usize = self.size.min(buf.len()).min(self.input.len());
+            buf[..len].copy_from_slice(&self.input[..len]);
+            self.input = &self.input[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_solution_1000000() {
+        let expected_output: String = read_to_string("resources/results_1000000.txt").unwrap();
+        let mut expected: Vec<&str> = expected_output
+            .lines()
+            .filter(|line: &&str| !line.is_empty())
+            .collect();
+        expected.sort_by_key(|line: &&str| line.split_once('=').unwrap().0);
+
+        let actual: Aggregation =
+            SolutionStreaming::solve_obrc("resources/measurements_1000000.txt").unwrap();
+
+        assert_eq!(expected.join("\n") + "\n", format(&actual, Format::Text));
+    }
+
+    #[test]
+    fn test_solution_malformed() {
+        assert!(matches!(
+            SolutionStreaming::solve_obrc("resources/measurements_malformed.txt"),
+            Err(ObrcError::MalformedLine { line, offset: 26, line_number: 3 }) if line == "Hamburg12.0"
+        ));
+    }
+
+    #[test]
+    fn test_solution_buffer_sizes() {
+        let input: Vec<u8> = read("resources/measurements_20.txt").unwrap();
+        let expected: Aggregation =
+            SolutioBasic::solve_obrc("resources/measurements_20.txt").unwrap();
+
+        for buffer_size in 1..=64 {
+            assert_eq!(
+                expected,
+                solve(input.as_slice(), buffer_size, &SolverOptions::default()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_solve_reader() {
+        let input: &[u8] = b"Hamburg;12.0\nBulawayo;8.9\nHamburg;-3.4\n";
+
+        let actual: Aggregation = SolutionStreaming::solve_reader(input).unwrap();
+
+        assert_eq!(
+            "{Bulawayo=8.9/8.9/8.9, Hamburg=-3.4/4.3/12.0}\n",
+            format(&actual, Format::Official)
+        );
+    }
+
+    #[test]
+    fn test_solution_small_reads() {
+        let input: Vec<u8> = read("resources/measurements_20.txt").unwrap();
+        let expected: Aggregation =
+            SolutioBasic::solve_obrc("resources/measurements_20.txt").unwrap();
+
+        for size in 1..=64 {
+            let reader: SmallReads<'_> = SmallReads {
+                input: &input,
+                size,
+            };
+            assert_eq!(
+                expected,
+                solve(reader, 1, &SolverOptions::default()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_solution_too_long_lines() {
+        let long_line: String = "x".repeat(1000) + ";1.0";
+        let path: String = temp_dir()
+            .join(format!("obrc_streaming_long_{}.txt", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let quarantine: SolverOptions = SolverOptions {
+            on_error: OnError::Quarantine(path.clone()),
+            ..SolverOptions::default()
+        };
+
+        for ending in ["\nBulawayo;8.9\n", ""] {
+            let input: String = format!("Hamburg;12.0\n{}{}", long_line, ending);
+            for size in [1, 7, 4096] {
+                let reader = || -> SmallReads<'_> {
+                    SmallReads {
+                        input: input.as_bytes(),
+                        size,
+                    }
+                };
+
+                assert!(matches!(
+                    solve(reader(), 1, &SolverOptions::default()),
+                    Err(ObrcError::MalformedLine { line, offset: 13, line_number: 2 })
+                        if line.len() == MAX_LINE_LENGTH + 1
+                ));
+                let actual: Aggregation = solve(reader(), 1, &quarantine).unwrap();
+                let quarantined: Vec<u8> = read(&path).unwrap();
+                assert_eq!(1, actual.rejected);
+                assert_eq!(
+                    SolutionStreaming::solve_reader(
+                        format!("Hamburg;12.0\n{}", ending.trim_start_matches('\n')).as_bytes()
+                    )
+                    .unwrap()
+                    .stations,
+                    actual.stations
+                );
+                assert_eq!(format!("{}\n", long_line).into_bytes(), quarantined);
+            }
+        }
+        remove_file(&path).unwrap();
+    }
+}
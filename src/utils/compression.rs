@@ -0,0 +1,428 @@
+//! Transparent decompression of the measurements, detected by the magic bytes they start with,
+//! and zstd compression of generated measurements in independent frames.
+//! Codecs are behind the `gzip`, `zstd` and `lz4` features; without them, compressed input is rejected.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fs::File;
+use std::io;
+use std::io::stdin;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::io::Read;
+#[cfg(feature = "zstd")]
+use std::io::Write;
+
+#[cfg(feature = "zstd")]
+use std::collections::VecDeque;
+
+#[cfg(feature = "zstd")]
+use memmap2::Mmap;
+#[cfg(feature = "zstd")]
+use rayon::prelude::*;
+
+use crate::solutions::error::ObrcError;
+
+/// Compression of the measurements.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    /// Plain text.
+    None,
+    /// gzip, possibly several concatenated members, as written by `pigz` or `bgzip`.
+    Gzip,
+    /// Zstandard, possibly several frames, decoded in parallel when read from a file.
+    Zstd,
+    /// LZ4 frame format.
+    Lz4,
+}
+
+impl Compression {
+    /// The compression of data starting with the given bytes, at least 4 of them for a reliable result.
+    pub fn detect(header: &[u8]) -> Compression {
+        match header {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+            [0x04, 0x22, 0x4d, 0x18, ..] => Compression::Lz4,
+            _ => Compression::None,
+        }
+    }
+
+    /// The compression of the file at the given path.
+    pub fn of_file(path: &str) -> Result<Compression, ObrcError> {
+        let mut header: Vec<u8> = Vec::with_capacity(4);
+        File::open(path)?.take(4).read_to_end(&mut header)?;
+        Ok(Compression::detect(&header))
+    }
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+            Compression::Lz4 => write!(f, "lz4"),
+        }
+    }
+}
+
+/// Open the measurements at the given path, or stdin for `-`, decompressing them on the fly.
+/// Multi-frame zstd files are decoded in parallel with [`ZstdFrames`].
+pub fn open_input(path: &str) -> Result<Box<dyn Read>, ObrcError> {
+    if path == "-" {
+        return decompress(stdin().lock());
+    }
+    let file: File = File::open(path)?;
+    #[cfg(feature = "zstd")]
+    if Compression::of_file(path)? == Compression::Zstd {
+        // SAFETY: the mapping is read-only and the input file is not expected to be modified concurrently.
+        let mmap: Mmap = unsafe { Mmap::map(&file)? };
+        if zstd_frame_size(&mmap)? < mmap.len() {
+            return Ok(Box::new(ZstdFrames::new(mmap)));
+        }
+    }
+    decompress(BufReader::new(file))
+}
+
+/// Decompress the reader according to its first bytes, or pass it through if it is plain text.
+pub fn decompress<'a, R: BufRead + 'a>(mut reader: R) -> Result<Box<dyn Read + 'a>, ObrcError> {
+    match Compression::detect(reader.fill_buf()?) {
+        Compression::None => Ok(Box::new(reader)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::bufread::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => Ok(Box::new(lz4_flex::frame::FrameDecoder::new(reader))),
+        #[allow(unreachable_patterns)]
+        compression => Err(io::Error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "{} input requires the `{}` feature",
+                compression, compression
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Size of the first zstd frame of the input, skippable frames included.
+#[cfg(feature = "zstd")]
+fn zstd_frame_size(input: &[u8]) -> io::Result<usize> {
+    zstd::zstd_safe::find_frame_compressed_size(input).map_err(|code: usize| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            zstd::zstd_safe::get_error_name(code),
+        )
+    })
+}
+
+/// Reads the decompressed contents of a multi-frame zstd input,
+/// decoding as many frames at once as there are rayon threads and yielding them in order.
+/// Holds that many decompressed frames in memory, so frames are expected to be of moderate size.
+#[cfg(feature = "zstd")]
+pub struct ZstdFrames<T: AsRef<[u8]>> {
+    input: T,
+    offset: usize,
+    frames: VecDeque<Vec<u8>>,
+    current: Vec<u8>,
+    position: usize,
+}
+
+#[cfg(feature = "zstd")]
+impl<T: AsRef<[u8]>> ZstdFrames<T> {
+    pub fn new(input: T) -> ZstdFrames<T> {
+        ZstdFrames {
+            input,
+            offset: 0,
+            frames: VecDeque::new(),
+            current: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Decode the next frames in parallel.
+    fn decode_frames(&mut self) -> io::Result<()> {
+        let input: &[u8] = self.input.as_ref();
+        let mut frames: Vec<&[u8]> = Vec::with_capacity(rayon::current_num_threads());
+        while frames.len() < rayon::current_num_threads() && self.offset < input.len() {
+            let size: usize = zstd_frame_size(&input[self.offset..])?;
+            frames.push(&input[self.offset..self.offset + size]);
+            self.offset += size;
+        }
+        let decoded: Vec<Vec<u8>> = frames
+            .par_iter()
+            .map(|frame: &&[u8]| zstd::stream::decode_all(*frame))
+            .collect::<io::Result<Vec<Vec<u8>>>>()?;
+        self.frames.extend(decoded);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<T: AsRef<[u8]>> Read for ZstdFrames<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.current.len() {
+            if self.frames.is_empty() {
+                if self.offset == self.input.as_ref().len() {
+                    return Ok(0);
+                }
+                self.decode_frames()?;
+            }
+            if let Some(frame) = self.frames.pop_front() {
+                self.current = frame;
+                self.position = 0;
+            }
+        }
+        let read: usize = buf.len().min(self.current.len() - self.position);
+        buf[..read].copy_from_slice(&self.current[self.position..self.position + read]);
+        self.position += read;
+        Ok(read)
+    }
+}
+
+/// Compresses measurements with zstd, in independent frames of a given number of lines,
+/// so that [`ZstdFrames`] can decode them in parallel and the file can be split at frame boundaries.
+#[cfg(feature = "zstd")]
+pub struct ZstdFrameWriter<W: Write> {
+    /// The writer, while no frame is open.
+    writer: Option<W>,
+    /// The open frame.
+    encoder: Option<zstd::stream::write::Encoder<'static, W>>,
+    level: i32,
+    threads: u32,
+    frame_lines: u64,
+    lines: u64,
+}
+
+#[cfg(feature = "zstd")]
+impl<W: Write> ZstdFrameWriter<W> {
+    /// Compress at the given level, ending a frame every `frame_lines` lines (at least 1),
+    /// each frame being compressed with the given number of threads.
+    pub fn new(writer: W, level: i32, frame_lines: u64, threads: u32) -> ZstdFrameWriter<W> {
+        ZstdFrameWriter {
+            writer: Some(writer),
+            encoder: None,
+            level,
+            threads,
+            frame_lines: frame_lines.max(1),
+            lines: 0,
+        }
+    }
+
+    /// End the last frame and return the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.encoder.take() {
+            Some(encoder) => encoder.finish(),
+            None => Ok(self.writer.take().unwrap()),
+        }
+    }
+
+    fn encoder(&mut self) -> io::Result<&mut zstd::stream::write::Encoder<'static, W>> {
+        if let Some(writer) = self.writer.take() {
+            let mut encoder: zstd::stream::write::Encoder<'static, W> =
+                zstd::stream::write::Encoder::new(writer, self.level)?;
+            encoder.include_checksum(true)?;
+            if self.threads > 1 {
+                encoder.multithread(self.threads)?;
+            }
+            self.encoder = Some(encoder);
+        }
+        Ok(self.encoder.as_mut().unwrap())
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<W: Write> Write for ZstdFrameWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let remaining: usize = (self.frame_lines - self.lines) as usize;
+        let frame_end: Option<usize> = buf
+            .iter()
+            .enumerate()
+            .filter(|(_, byte): &(usize, &u8)| **byte == b'\n')
+            .nth(remaining - 1)
+            .map(|(position, _): (usize, &u8)| position + 1);
+        match frame_end {
+            Some(frame_end) => {
+                self.encoder()?.write_all(&buf[..frame_end])?;
+                self.writer = Some(self.encoder.take().unwrap().finish()?);
+                self.lines = 0;
+                Ok(frame_end)
+            }
+            None => {
+                self.encoder()?.write_all(buf)?;
+                self.lines += buf.iter().filter(|byte: &&u8| **byte == b'\n').count() as u64;
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match (&mut self.encoder, &mut self.writer) {
+            (Some(encoder), _) => encoder.flush(),
+            (None, Some(writer)) => writer.flush(),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read;
+
+    use super::decompress;
+    use super::Compression;
+    use crate::solutions::aggregation::Aggregation;
+    use crate::solutions::solution_basic::SolutioBasic;
+    use crate::solutions::solution_streaming::SolutionStreaming;
+    use crate::solutions::solver::Solver;
+
+    const INPUT_PATH: &str = "resources/measurements_20.txt";
+
+    fn expected() -> Aggregation {
+        SolutioBasic::solve_obrc(INPUT_PATH).unwrap()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            Compression::Gzip,
+            Compression::detect(&[0x1f, 0x8b, 0x08, 0x00])
+        );
+        assert_eq!(
+            Compression::Zstd,
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd])
+        );
+        assert_eq!(
+            Compression::Lz4,
+            Compression::detect(&[0x04, 0x22, 0x4d, 0x18])
+        );
+        assert_eq!(Compression::None, Compression::detect(b"Hamburg;12.0\n"));
+        assert_eq!(Compression::None, Compression::detect(b""));
+        assert_eq!(Compression::None, Compression::of_file(INPUT_PATH).unwrap());
+    }
+
+    #[test]
+    fn test_decompress_plain() {
+        let input: Vec<u8> = read(INPUT_PATH).unwrap();
+
+        let actual: Aggregation =
+            SolutionStreaming::solve_reader(decompress(input.as_slice()).unwrap()).unwrap();
+
+        assert_eq!(expected(), actual);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_decompress_unsupported() {
+        assert!(decompress([0x1f, 0x8b, 0x08, 0x00].as_slice()).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decompress_gzip() {
+        use std::io::Write;
+
+        use flate2::write::GzEncoder;
+
+        let input: Vec<u8> = read(INPUT_PATH).unwrap();
+        let (first, second) = input.split_at(input.len() / 2);
+        // Two concatenated members, split within a line.
+        let mut compressed: Vec<u8> = Vec::new();
+        for part in [first, second] {
+            let mut encoder: GzEncoder<Vec<u8>> =
+                GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(part).unwrap();
+            compressed.extend(encoder.finish().unwrap());
+        }
+
+        let actual: Aggregation =
+            SolutionStreaming::solve_reader(decompress(compressed.as_slice()).unwrap()).unwrap();
+
+        assert_eq!(expected(), actual);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_decompress_zstd_frames() {
+        use super::ZstdFrames;
+
+        let input: Vec<u8> = read(INPUT_PATH).unwrap();
+        // Frames of 7 bytes, mostly split within lines, and an empty one.
+        let mut compressed: Vec<u8> = zstd::stream::encode_all(&b""[..], 0).unwrap();
+        for chunk in input.chunks(7) {
+            compressed.extend(zstd::stream::encode_all(chunk, 0).unwrap());
+        }
+
+        let actual: Aggregation =
+            SolutionStreaming::solve_reader(decompress(compressed.as_slice()).unwrap()).unwrap();
+        assert_eq!(expected(), actual);
+
+        let actual: Aggregation =
+            SolutionStreaming::solve_reader(ZstdFrames::new(compressed)).unwrap();
+        assert_eq!(expected(), actual);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_frame_writer() {
+        use std::io::Read;
+        use std::io::Write;
+
+        use super::zstd_frame_size;
+        use super::ZstdFrameWriter;
+        use super::ZstdFrames;
+
+        let input: Vec<u8> = read(INPUT_PATH).unwrap();
+        let mut writer: ZstdFrameWriter<Vec<u8>> = ZstdFrameWriter::new(Vec::new(), 3, 4, 1);
+        // Writes of 5 bytes, across line and frame boundaries.
+        input
+            .chunks(5)
+            .for_each(|chunk: &[u8]| writer.write_all(chunk).unwrap());
+        let compressed: Vec<u8> = writer.finish().unwrap();
+
+        let mut offset: usize = 0;
+        let mut frames: Vec<Vec<u8>> = Vec::new();
+        while offset < compressed.len() {
+            let size: usize = zstd_frame_size(&compressed[offset..]).unwrap();
+            frames.push(zstd::stream::decode_all(&compressed[offset..offset + size]).unwrap());
+            offset += size;
+        }
+        assert_eq!(
+            input
+                .iter()
+                .filter(|byte: &&u8| **byte == b'\n')
+                .count()
+                .div_ceil(4),
+            frames.len()
+        );
+        assert!(frames[..frames.len() - 1].iter().all(|frame: &Vec<u8>| {
+            frame.iter().filter(|byte: &&u8| **byte == b'\n').count() == 4 && frame.ends_with(b"\n")
+        }));
+
+        let mut output: Vec<u8> = Vec::new();
+        ZstdFrames::new(compressed)
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(input, output);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_decompress_lz4() {
+        use std::io::Write;
+
+        use lz4_flex::frame::FrameEncoder;
+
+        let input: Vec<u8> = read(INPUT_PATH).unwrap();
+        let mut encoder: FrameEncoder<Vec<u8>> = FrameEncoder::new(Vec::new());
+        encoder.write_all(&input).unwrap();
+        let compressed: Vec<u8> = encoder.finish().unwrap();
+
+        let actual: Aggregation =
+            SolutionStreaming::solve_reader(decompress(compressed.as_slice()).unwrap()).unwrap();
+
+        assert_eq!(expected(), actual);
+    }
+}
@@ -9,3 +9,125 @@ where
 {
     FromStr::from_str(text.trim()).unwrap()
 }
+
+/// Parse a temperature matching `-?\d?\d\.\d` into tenths of a degree, e.g. `-12.3` into `-123`.
+/// The fractional digit may also be left out (`-12`), as `generate_input` writes whole degrees that way.
+/// Returns `None` for anything else, including surrounding whitespace.
+#[inline]
+pub fn parse_temperature(bytes: &[u8]) -> Option<i16> {
+    let (negative, digits) = match bytes {
+        [b'-', digits @ ..] => (true, digits),
+        digits => (false, digits),
+    };
+
+    let tenths: i16 = match *digits {
+        [units, b'.', tenths] => digit(units)? * 10 + digit(tenths)?,
+        [tens, units, b'.', tenths] => digit(tens)? * 100 + digit(units)? * 10 + digit(tenths)?,
+        [units] => digit(units)? * 10,
+        [tens, units] => digit(tens)? * 100 + digit(units)? * 10,
+        _ => return None,
+    };
+
+    Some(if negative { -tenths } else { tenths })
+}
+
+/// Parse a `<station name>;<temperature>` line, without its newline, into the name
+/// and the temperature in tenths of a degree, see [`parse_temperature`].
+/// Returns `None` if the `;` is missing, the name is not UTF-8 or the temperature is invalid.
+#[inline]
+pub fn parse_line(line: &[u8]) -> Option<(&str, i16)> {
+    let separator: usize = line.iter().position(|byte: &u8| *byte == b';')?;
+    let name: &str = std::str::from_utf8(&line[..separator]).ok()?;
+    Some((name, parse_temperature(&line[separator + 1..])?))
+}
+
+#[inline(always)]
+fn digit(byte: u8) -> Option<i16> {
+    byte.is_ascii_digit().then(|| (byte - b'0') as i16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_line;
+    use super::parse_temperature;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(Some(("Hamburg", 120)), parse_line(b"Hamburg;12.0"));
+        assert_eq!(Some(("St. John's", -34)), parse_line(b"St. John's;-3.4"));
+        assert_eq!(Some(("", 10)), parse_line(b";1.0"));
+        assert_eq!(Some(("a", 10)), parse_line(b"a;1.0"));
+        for line in [
+            b"Hamburg12.0".as_slice(),
+            b"Hamburg;12.0;1.0",
+            b"Hamburg;",
+            b"Z\xfcrich;1.0",
+            b"",
+        ] {
+            assert_eq!(None, parse_line(line), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse_temperature_all_values() {
+        for tenths in -999_i16..=999 {
+            let text: String = format!(
+                "{}{}.{}",
+                if tenths < 0 { "-" } else { "" },
+                tenths.abs() / 10,
+                tenths.abs() % 10
+            );
+            assert_eq!(Some(tenths), parse_temperature(text.as_bytes()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_temperature_all_values_with_leading_zero() {
+        for tenths in -99_i16..=99 {
+            let text: String = format!(
+                "{}0{}.{}",
+                if tenths < 0 { "-" } else { "" },
+                tenths.abs() / 10,
+                tenths.abs() % 10
+            );
+            assert_eq!(Some(tenths), parse_temperature(text.as_bytes()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_temperature_whole_degrees() {
+        for degrees in -99_i16..=99 {
+            let text: String = degrees.to_string();
+            assert_eq!(
+                Some(degrees * 10),
+                parse_temperature(text.as_bytes()),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_temperature_matches_f64() {
+        for tenths in -999_i16..=999 {
+            let text: String = format!("{:.1}", tenths as f64 / 10.0);
+            let expected: i16 = (text.parse::<f64>().unwrap() * 10.0).round() as i16;
+            assert_eq!(
+                Some(expected),
+                parse_temperature(text.as_bytes()),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_temperature_invalid() {
+        for text in [
+            "", "-", ".", "-.", "1.", "-1.", ".5", "-.5", "100.0", "-100.0", "100", "1.23", "a.b",
+            "1.a", "--1.0", "+1.0", "1,0", " 1.0", "1.0 ", "1.0\r", "-1-0", "1..0",
+        ] {
+            assert_eq!(None, parse_temperature(text.as_bytes()), "{:?}", text);
+        }
+    }
+}
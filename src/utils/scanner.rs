@@ -0,0 +1,289 @@
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// Find the first position of either `first` or `second` in `haystack`.
+type FindFn = unsafe fn(haystack: &[u8], first: u8, second: u8) -> Option<usize>;
+
+/// Name and temperature bytes of a record, or the whole line if it has no `;`.
+pub type Record<'a> = Result<(&'a [u8], &'a [u8]), &'a [u8]>;
+
+/// Implementation used to look for `;` and `\n`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// One byte at a time.
+    Scalar,
+    /// Eight bytes at a time, in a `u64`.
+    Swar,
+    /// 16 bytes at a time, with SSE2.
+    Sse2,
+    /// 32 bytes at a time, with AVX2.
+    Avx2,
+}
+
+impl Backend {
+    /// The fastest implementation supported by the current CPU.
+    pub fn detect() -> Backend {
+        [Backend::Avx2, Backend::Sse2]
+            .into_iter()
+            .find(|backend: &Backend| backend.is_supported())
+            .unwrap_or(Backend::Swar)
+    }
+
+    /// Whether the current CPU can run this implementation.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Backend::Scalar | Backend::Swar => true,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::Sse2 | Backend::Avx2 => false,
+        }
+    }
+
+    fn find_fn(&self) -> FindFn {
+        match self {
+            Backend::Scalar => find_scalar,
+            Backend::Swar => find_swar,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Sse2 => find_sse2,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => find_avx2,
+            #[cfg(not(target_arch = "x86_64"))]
+            Backend::Sse2 | Backend::Avx2 => unreachable!(),
+        }
+    }
+}
+
+/// Iterator over the `<station name>;<temperature>` records of the input, separated by `\n`.
+/// Empty lines are skipped.
+pub struct Records<'a> {
+    input: &'a [u8],
+    find: FindFn,
+}
+
+impl<'a> Records<'a> {
+    /// Scan the input with the fastest implementation supported by the current CPU.
+    pub fn new(input: &'a [u8]) -> Records<'a> {
+        Records::with_backend(input, Backend::detect())
+    }
+
+    /// Scan the input with the given implementation.
+    ///
+    /// # Panics
+    /// If the current CPU does not support the implementation.
+    pub fn with_backend(input: &'a [u8], backend: Backend) -> Records<'a> {
+        assert!(
+            backend.is_supported(),
+            "{:?} is not supported by this CPU",
+            backend
+        );
+        Records {
+            input,
+            find: backend.find_fn(),
+        }
+    }
+
+    #[inline]
+    fn find_either(&self, haystack: &[u8], first: u8, second: u8) -> Option<usize> {
+        // SAFETY: `with_backend` checked that the CPU supports the implementation.
+        unsafe { (self.find)(haystack, first, second) }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Record<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.input.first() == Some(&b'\n') {
+            self.input = &self.input[1..];
+        }
+        if self.input.is_empty() {
+            return None;
+        }
+
+        let input: &'a [u8] = self.input;
+        match self.find_either(input, b';', b'\n') {
+            Some(separator) if input[separator] == b';' => {
+                let end: usize = self
+                    .find_either(&input[separator + 1..], b'\n', b'\n')
+                    .map_or(input.len(), |position: usize| separator + 1 + position);
+                self.input = &input[(end + 1).min(input.len())..];
+                Some(Ok((&input[..separator], &input[separator + 1..end])))
+            }
+            newline => {
+                let end: usize = newline.unwrap_or(input.len());
+                self.input = &input[(end + 1).min(input.len())..];
+                Some(Err(&input[..end]))
+            }
+        }
+    }
+}
+
+unsafe fn find_scalar(haystack: &[u8], first: u8, second: u8) -> Option<usize> {
+    haystack
+        .iter()
+        .position(|byte: &u8| *byte == first || *byte == second)
+}
+
+const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+/// High bit set in every zero byte of `word`. Bytes above the lowest zero byte may be false positives.
+#[inline(always)]
+fn zero_bytes(word: u64) -> u64 {
+    word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS
+}
+
+unsafe fn find_swar(haystack: &[u8], first: u8, second: u8) -> Option<usize> {
+    let first_bytes: u64 = LOW_BITS * first as u64;
+    let second_bytes: u64 = LOW_BITS * second as u64;
+    let mut chunks = haystack.chunks_exact(8);
+
+    for (index, chunk) in chunks.by_ref().enumerate() {
+        let word: u64 = u64::from_le_bytes(chunk.try_into().unwrap());
+        let mask: u64 = zero_bytes(word ^ first_bytes) | zero_bytes(word ^ second_bytes);
+        if mask != 0 {
+            return Some(index * 8 + mask.trailing_zeros() as usize / 8);
+        }
+    }
+
+    let offset: usize = haystack.len() - chunks.remainder().len();
+    find_scalar(chunks.remainder(), first, second).map(|position: usize| offset + position)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn find_sse2(haystack: &[u8], first: u8, second: u8) -> Option<usize> {
+    let first_bytes: __m128i = _mm_set1_epi8(first as i8);
+    let second_bytes: __m128i = _mm_set1_epi8(second as i8);
+    let mut offset: usize = 0;
+
+    while offset + 16 <= haystack.len() {
+        let chunk: __m128i = _mm_loadu_si128(haystack.as_ptr().add(offset) as *const __m128i);
+        let mask: i32 = _mm_movemask_epi8(_mm_or_si128(
+            _mm_cmpeq_epi8(chunk, first_bytes),
+            _mm_cmpeq_epi8(chunk, second_bytes),
+        ));
+        if mask != 0 {
+            return Some(offset + mask.trailing_zeros() as usize);
+        }
+        offset += 16;
+    }
+
+    find_swar(&haystack[offset..], first, second).map(|position: usize| offset + position)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn find_avx2(haystack: &[u8], first: u8, second: u8) -> Option<usize> {
+    let first_bytes: __m256i = _mm256_set1_epi8(first as i8);
+    let second_bytes: __m256i = _mm256_set1_epi8(second as i8);
+    let mut offset: usize = 0;
+
+    while offset + 32 <= haystack.len() {
+        let chunk: __m256i = _mm256_loadu_si256(haystack.as_ptr().add(offset) as *const __m256i);
+        let mask: i32 = _mm256_movemask_epi8(_mm256_or_si256(
+            _mm256_cmpeq_epi8(chunk, first_bytes),
+            _mm256_cmpeq_epi8(chunk, second_bytes),
+        ));
+        if mask != 0 {
+            return Some(offset + mask.trailing_zeros() as usize);
+        }
+        offset += 32;
+    }
+
+    find_sse2(&haystack[offset..], first, second).map(|position: usize| offset + position)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::find_scalar;
+    use super::Backend;
+    use super::Record;
+    use super::Records;
+
+    const BACKENDS: [Backend; 4] = [Backend::Scalar, Backend::Swar, Backend::Sse2, Backend::Avx2];
+
+    fn supported_backends() -> impl Iterator<Item = Backend> {
+        BACKENDS
+            .into_iter()
+            .filter(|backend: &Backend| backend.is_supported())
+    }
+
+    fn random_input(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|_| match rng.gen_range(0..20) {
+                0 => b';',
+                1 => b'\n',
+                2 => 0xFF,
+                _ => rng.gen_range(b'a'..=b'z'),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_matches_scalar() {
+        let mut rng: StdRng = StdRng::seed_from_u64(42);
+        for len in 0..200 {
+            let input: Vec<u8> = random_input(&mut rng, len);
+            for start in 0..=len {
+                let expected: Option<usize> = unsafe { find_scalar(&input[start..], b';', b'\n') };
+                for backend in supported_backends() {
+                    let actual: Option<usize> =
+                        unsafe { (backend.find_fn())(&input[start..], b';', b'\n') };
+                    assert_eq!(expected, actual, "{:?} {:?}", backend, &input[start..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_records_match_scalar() {
+        let mut rng: StdRng = StdRng::seed_from_u64(42);
+        for len in (0..2000).step_by(7) {
+            let input: Vec<u8> = random_input(&mut rng, len);
+            let expected: Vec<_> = Records::with_backend(&input, Backend::Scalar).collect();
+            for backend in supported_backends() {
+                let actual: Vec<_> = Records::with_backend(&input, backend).collect();
+                assert_eq!(expected, actual, "{:?}", backend);
+            }
+        }
+    }
+
+    #[test]
+    fn test_records() {
+        let input: &[u8] =
+            "Hamburg;12.0\n\nBulawayo;8.9\nHamburg12.0\nİzmir;-15.0\nSt. John's;15.2\n;1.0\nZürich;"
+                .as_bytes();
+        let expected: Vec<Record<'_>> = vec![
+            Ok((b"Hamburg", b"12.0")),
+            Ok((b"Bulawayo", b"8.9")),
+            Err(b"Hamburg12.0"),
+            Ok(("İzmir".as_bytes(), b"-15.0")),
+            Ok((b"St. John's", b"15.2")),
+            Ok((b"", b"1.0")),
+            Ok(("Zürich".as_bytes(), b"")),
+        ];
+
+        for backend in supported_backends() {
+            let actual: Vec<_> = Records::with_backend(input, backend).collect();
+            assert_eq!(expected, actual, "{:?}", backend);
+        }
+        assert_eq!(0, Records::new(b"").count());
+        assert_eq!(0, Records::new(b"\n\n").count());
+    }
+
+    #[test]
+    fn test_detect() {
+        assert!(Backend::detect().is_supported());
+        #[cfg(target_arch = "x86_64")]
+        assert_ne!(Backend::Swar, Backend::detect());
+    }
+}
@@ -0,0 +1,261 @@
+use crate::solutions::aggregation::Statistics;
+
+/// Maximum number of distinct weather stations allowed by the challenge.
+pub const MAX_STATIONS: usize = 10_000;
+
+/// Number of slots: a power of two, so that probing can mask instead of dividing,
+/// and large enough to keep the load factor around 0.3 with `MAX_STATIONS` stations.
+const CAPACITY: usize = 1 << 15;
+
+/// Names up to this length are stored in the slot itself, longer ones on the heap.
+const INLINE_NAME_LEN: usize = 30;
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// Hash the station name at the start of `line`, while scanning for the `;` that ends it.
+/// Returns the length of the name and its hash,
+/// or `None` if the line (or the input) ends before a `;` is found.
+#[inline]
+pub fn hash_name(line: &[u8]) -> Option<(usize, u64)> {
+    let mut hash: u64 = 0;
+    for (index, byte) in line.iter().enumerate() {
+        match *byte {
+            b';' => return Some((index, hash)),
+            b'\n' => return None,
+            byte => hash = (hash.rotate_left(5) ^ byte as u64).wrapping_mul(FX_SEED),
+        }
+    }
+    None
+}
+
+enum Name {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_NAME_LEN],
+    },
+    Heap(Box<[u8]>),
+}
+
+impl Name {
+    fn new(name: &[u8]) -> Name {
+        if name.len() <= INLINE_NAME_LEN {
+            let mut bytes: [u8; INLINE_NAME_LEN] = [0; INLINE_NAME_LEN];
+            bytes[..name.len()].copy_from_slice(name);
+            Name::Inline {
+                len: name.len() as u8,
+                bytes,
+            }
+        } else {
+            Name::Heap(name.into())
+        }
+    }
+
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Name::Inline { len, bytes } => &bytes[..*len as usize],
+            Name::Heap(bytes) => bytes,
+        }
+    }
+}
+
+struct Key {
+    hash: u64,
+    name: Name,
+}
+
+/// Open-addressing hash table from station name bytes to their statistics,
+/// with linear probing over a fixed number of slots.
+/// Hashes are computed by the caller, usually with [`hash_name`] while parsing the line.
+pub struct StationTable {
+    keys: Vec<Option<Key>>,
+    stats: Vec<Statistics>,
+    len: usize,
+    empty: Statistics,
+}
+
+impl StationTable {
+    pub fn new() -> StationTable {
+        StationTable::with_statistics(Statistics::default())
+    }
+
+    /// Table whose stations start from a copy of `empty`,
+    /// e.g. to also keep a histogram of the temperatures.
+    pub fn with_statistics(empty: Statistics) -> StationTable {
+        StationTable {
+            keys: (0..CAPACITY).map(|_| None).collect(),
+            stats: vec![Statistics::default(); CAPACITY],
+            len: 0,
+            empty,
+        }
+    }
+
+    /// Number of stations in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Statistics of the station with the given name and hash, if present.
+    #[inline]
+    pub fn get_mut(&mut self, name: &[u8], hash: u64) -> Option<&mut Statistics> {
+        let mut index: usize = hash as usize & (CAPACITY - 1);
+        loop {
+            match &self.keys[index] {
+                Some(key) if key.hash == hash && key.name.as_bytes() == name => {
+                    return Some(&mut self.stats[index])
+                }
+                Some(_) => index = (index + 1) & (CAPACITY - 1),
+                None => return None,
+            }
+        }
+    }
+
+    /// Statistics of the station with the given name and hash, inserted if missing.
+    /// Returns `None` if the station is missing and the table already holds `MAX_STATIONS` stations.
+    #[inline]
+    pub fn entry(&mut self, name: &[u8], hash: u64) -> Option<&mut Statistics> {
+        let mut index: usize = hash as usize & (CAPACITY - 1);
+        loop {
+            match &self.keys[index] {
+                Some(key) if key.hash == hash && key.name.as_bytes() == name => break,
+                Some(_) => index = (index + 1) & (CAPACITY - 1),
+                None if self.len == MAX_STATIONS => return None,
+                None => {
+                    self.keys[index] = Some(Key {
+                        hash,
+                        name: Name::new(name),
+                    });
+                    self.stats[index] = self.empty.clone();
+                    self.len += 1;
+                    break;
+                }
+            }
+        }
+        Some(&mut self.stats[index])
+    }
+
+    /// Combine the statistics of another table into this one.
+    /// Returns `None` if the combined table would exceed `MAX_STATIONS` stations.
+    pub fn merge(&mut self, other: &StationTable) -> Option<()> {
+        for (key, stats) in other.keys.iter().zip(&other.stats) {
+            if let Some(key) = key {
+                self.entry(key.name.as_bytes(), key.hash)?.merge(stats);
+            }
+        }
+        Some(())
+    }
+
+    /// Stations and their statistics, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &Statistics)> {
+        self.keys
+            .iter()
+            .zip(&self.stats)
+            .filter_map(|(key, stats)| key.as_ref().map(|key: &Key| (key.name.as_bytes(), stats)))
+    }
+}
+
+impl Default for StationTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash_name;
+    use super::StationTable;
+    use super::MAX_STATIONS;
+
+    #[test]
+    fn test_hash_name() {
+        let (len, hash) = hash_name(b"Hamburg;12.0\n").unwrap();
+        assert_eq!(7, len);
+        assert_eq!(Some((7, hash)), hash_name(b"Hamburg;-3.4"));
+        assert_ne!(hash, hash_name(b"Hamburh;12.0").unwrap().1);
+        assert_eq!(Some((0, 0)), hash_name(b";12.0"));
+        assert_eq!(None, hash_name(b"Hamburg12.0\nBulawayo;8.9"));
+        assert_eq!(None, hash_name(b"Hamburg"));
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut table: StationTable = StationTable::new();
+        let long_name: &[u8] =
+            "Llanfairpwllgwyngyllgogerychwyrndrobwllllantysiliogogogoch".as_bytes();
+
+        for (name, temperature) in [
+            (b"Hamburg".as_slice(), 120),
+            (long_name, -15),
+            (b"Hamburg".as_slice(), -34),
+            (long_name, 25),
+        ] {
+            let (_, hash) = hash_name(&[name, b";"].concat()).unwrap();
+            table.entry(name, hash).unwrap().update(temperature);
+        }
+
+        assert_eq!(2, table.len());
+        let mut stations: Vec<(&[u8], u64, f64, f64)> = table
+            .iter()
+            .map(|(name, stats)| (name, stats.count(), stats.min(), stats.max()))
+            .collect();
+        stations.sort_by_key(|station| station.0);
+        assert_eq!(
+            vec![
+                (b"Hamburg".as_slice(), 2, -3.4, 12.0),
+                (long_name, 2, -1.5, 2.5)
+            ],
+            stations
+        );
+    }
+
+    #[test]
+    fn test_entry_collisions() {
+        let mut table: StationTable = StationTable::new();
+        for index in 0..100_u32 {
+            table
+                .entry(index.to_string().as_bytes(), 42)
+                .unwrap()
+                .update(index as i64);
+        }
+        for index in 0..100_u32 {
+            table
+                .entry(index.to_string().as_bytes(), 42)
+                .unwrap()
+                .update(index as i64);
+        }
+
+        assert_eq!(100, table.len());
+        assert!(table.iter().all(|(_, stats)| stats.count() == 2));
+    }
+
+    #[test]
+    fn test_entry_full() {
+        let mut table: StationTable = StationTable::new();
+        for index in 0..MAX_STATIONS as u64 {
+            assert!(table.entry(index.to_string().as_bytes(), index).is_some());
+        }
+
+        assert!(table.entry(b"0", 0).is_some());
+        assert!(table.entry(b"one too many", 0).is_none());
+        assert_eq!(MAX_STATIONS, table.len());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut table: StationTable = StationTable::new();
+        let mut other: StationTable = StationTable::new();
+        table.entry(b"Hamburg", 1).unwrap().update(120);
+        other.entry(b"Hamburg", 1).unwrap().update(-34);
+        other.entry(b"Bulawayo", 2).unwrap().update(89);
+
+        table.merge(&other).unwrap();
+
+        assert_eq!(2, table.len());
+        assert_eq!(2, table.entry(b"Hamburg", 1).unwrap().count());
+        assert_eq!(1, table.entry(b"Bulawayo", 2).unwrap().count());
+    }
+}